@@ -0,0 +1,212 @@
+// Copyright (c) The camino Contributors
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Paths that are anchored to a base directory.
+
+use crate::{JoinWithinError, Utf8Path, Utf8PathBuf};
+use std::{ffi::OsStr, fmt, ops::Deref, path::Path};
+
+/// A path relative to a base directory, which remembers both parts.
+///
+/// Tools often need two forms of the same path: a short relative one such as `src/lib.rs` for
+/// diagnostics and manifests, and the full one such as `/home/alice/ws/src/lib.rs` for filesystem
+/// calls. A `Utf8AnchoredPath` holds the base directory and the relative path, along with the
+/// joined path.
+///
+/// `Utf8AnchoredPath` [dereferences](Deref) to the joined path and implements
+/// [`AsRef<Utf8Path>`] and [`AsRef<Path>`], so it can be passed to filesystem APIs directly. The
+/// [`Display`](fmt::Display) implementation, and the [`Serialize`] implementation with the `serde1`
+/// feature, only show the relative part.
+///
+/// [`Serialize`]: https://docs.rs/serde/1/serde/trait.Serialize.html
+///
+/// # Examples
+///
+/// ```
+/// use camino::{Utf8AnchoredPath, Utf8Path};
+///
+/// let path = Utf8AnchoredPath::new("/home/alice/ws", "src/lib.rs").unwrap();
+///
+/// assert_eq!(path.base(), "/home/alice/ws");
+/// assert_eq!(path.rel(), "src/lib.rs");
+/// assert_eq!(path.to_string(), "src/lib.rs");
+///
+/// // Methods on Utf8Path operate on the joined path.
+/// assert_eq!(path.as_path(), "/home/alice/ws/src/lib.rs");
+/// assert_eq!(path.parent(), Some(Utf8Path::new("/home/alice/ws/src")));
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct Utf8AnchoredPath {
+    base: Utf8PathBuf,
+    rel: Utf8PathBuf,
+    full: Utf8PathBuf,
+}
+
+impl Utf8AnchoredPath {
+    /// Creates a new anchored path by joining `rel` onto `base`.
+    ///
+    /// `rel` is joined as with [`Utf8Path::join_within`]: `.` and `..` components are resolved,
+    /// and an error is returned if `rel` has a root or prefix, or if it would escape `base`. The
+    /// relative part of the result is the part of the joined path after `base`.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`JoinWithinError`] if `rel` isn't contained within `base`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use camino::Utf8AnchoredPath;
+    ///
+    /// let path = Utf8AnchoredPath::new("/ws", "src/../tests/./a.rs").unwrap();
+    /// assert_eq!(path.rel(), "tests/a.rs");
+    /// assert_eq!(path.as_path(), "/ws/tests/a.rs");
+    ///
+    /// assert!(Utf8AnchoredPath::new("/ws", "../other").is_err());
+    /// assert!(Utf8AnchoredPath::new("/ws", "/etc/passwd").is_err());
+    /// ```
+    pub fn new(
+        base: impl Into<Utf8PathBuf>,
+        rel: impl AsRef<Utf8Path>,
+    ) -> Result<Self, JoinWithinError> {
+        let base = base.into();
+        let full = base.join_within(rel)?;
+        let rel = full
+            .strip_prefix(&base)
+            .expect("join_within returns a path under base")
+            .to_path_buf();
+        Ok(Self { base, rel, full })
+    }
+
+    /// Creates a new anchored path from a full path that starts with `base`.
+    ///
+    /// The relative part is `full` with `base` [stripped](Utf8Path::strip_prefix) off. Returns
+    /// `None` if `full` doesn't start with `base`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use camino::Utf8AnchoredPath;
+    ///
+    /// let path = Utf8AnchoredPath::from_full("/ws", "/ws/src/main.rs").unwrap();
+    /// assert_eq!(path.rel(), "src/main.rs");
+    ///
+    /// assert_eq!(Utf8AnchoredPath::from_full("/ws", "/tmp/main.rs"), None);
+    /// ```
+    #[must_use]
+    pub fn from_full(base: impl Into<Utf8PathBuf>, full: impl Into<Utf8PathBuf>) -> Option<Self> {
+        let base = base.into();
+        let full = full.into();
+        let rel = full.strip_prefix(&base).ok()?.to_path_buf();
+        Some(Self { base, rel, full })
+    }
+
+    /// Returns the base directory.
+    #[inline]
+    #[must_use]
+    pub fn base(&self) -> &Utf8Path {
+        &self.base
+    }
+
+    /// Returns the path relative to the base directory.
+    #[inline]
+    #[must_use]
+    pub fn rel(&self) -> &Utf8Path {
+        &self.rel
+    }
+
+    /// Returns the full path, i.e. the relative path joined onto the base directory.
+    #[inline]
+    #[must_use]
+    pub fn as_path(&self) -> &Utf8Path {
+        &self.full
+    }
+
+    /// Joins `path` onto the relative part, keeping the same base directory.
+    ///
+    /// As with [`new`](Self::new), `path` must not escape the base directory, though it may
+    /// contain `..` components that stay within it.
+    ///
+    /// To join onto the full path instead, use [`Utf8Path::join`].
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`JoinWithinError`] if the result isn't contained within the base directory.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use camino::Utf8AnchoredPath;
+    ///
+    /// let src = Utf8AnchoredPath::new("/ws", "src").unwrap();
+    /// let lib = src.join_rel("lib.rs").unwrap();
+    /// assert_eq!(lib.rel(), "src/lib.rs");
+    /// assert_eq!(lib.as_path(), "/ws/src/lib.rs");
+    ///
+    /// assert_eq!(src.join_rel("../README.md").unwrap().rel(), "README.md");
+    /// assert!(src.join_rel("../../README.md").is_err());
+    /// ```
+    pub fn join_rel(&self, path: impl AsRef<Utf8Path>) -> Result<Self, JoinWithinError> {
+        Self::new(self.base.clone(), self.rel.join(path))
+    }
+
+    /// Consumes the anchored path, returning the full path.
+    #[inline]
+    #[must_use]
+    pub fn into_path_buf(self) -> Utf8PathBuf {
+        self.full
+    }
+
+    /// Consumes the anchored path, returning the base directory and the relative path.
+    #[inline]
+    #[must_use]
+    pub fn into_parts(self) -> (Utf8PathBuf, Utf8PathBuf) {
+        (self.base, self.rel)
+    }
+}
+
+impl Deref for Utf8AnchoredPath {
+    type Target = Utf8Path;
+
+    #[inline]
+    fn deref(&self) -> &Utf8Path {
+        &self.full
+    }
+}
+
+impl AsRef<Utf8Path> for Utf8AnchoredPath {
+    #[inline]
+    fn as_ref(&self) -> &Utf8Path {
+        &self.full
+    }
+}
+
+impl AsRef<Path> for Utf8AnchoredPath {
+    #[inline]
+    fn as_ref(&self) -> &Path {
+        self.full.as_std_path()
+    }
+}
+
+impl AsRef<OsStr> for Utf8AnchoredPath {
+    #[inline]
+    fn as_ref(&self) -> &OsStr {
+        self.full.as_os_str()
+    }
+}
+
+impl From<Utf8AnchoredPath> for Utf8PathBuf {
+    #[inline]
+    fn from(path: Utf8AnchoredPath) -> Utf8PathBuf {
+        path.into_path_buf()
+    }
+}
+
+/// Displays only the relative part of the path.
+///
+/// To display the full path, use [`as_path`](Utf8AnchoredPath::as_path).
+impl fmt::Display for Utf8AnchoredPath {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&self.rel, f)
+    }
+}
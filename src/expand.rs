@@ -0,0 +1,360 @@
+// Copyright (c) The camino Contributors
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Tilde and environment variable expansion for [`Utf8Path`].
+
+use crate::{Utf8Path, Utf8PathBuf};
+use std::{
+    error,
+    ffi::{OsStr, OsString},
+    fmt,
+    path::is_separator,
+};
+
+impl Utf8Path {
+    /// Expands a leading `~` and references to environment variables in the path.
+    ///
+    /// The following forms are recognized, subject to `options`:
+    ///
+    /// * `~` at the beginning of the path, followed by a separator or the end of the path, is
+    ///   replaced with the current user's home directory ([`ExpandKey::HomeDir`]).
+    /// * `~user` at the beginning of the path, followed by a separator or the end of the path, is
+    ///   replaced with the home directory of `user` ([`ExpandKey::UserHomeDir`]).
+    /// * `$VAR`, where `VAR` consists of ASCII letters, digits and underscores and doesn't start
+    ///   with a digit, is replaced with the value of the variable `VAR` ([`ExpandKey::Var`]). A
+    ///   `$` that isn't followed by a variable name is left as-is.
+    /// * `${VAR}` is replaced with the value of the variable `VAR`. Any characters other than `}`
+    ///   may appear in `VAR`.
+    /// * `%VAR%` is replaced with the value of the variable `VAR`. A `%` that isn't followed by a
+    ///   variable name and a closing `%` is left as-is.
+    ///
+    /// Values are obtained by calling `lookup`, which makes it possible to expand paths without
+    /// depending on the environment of the current process. Expanded values are inserted
+    /// literally and are not expanded again.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`ExpandError`] if:
+    ///
+    /// * `lookup` returns [`None`] for a key;
+    /// * `lookup` returns a value that isn't valid UTF-8;
+    /// * the path contains a `${` that isn't followed by a variable name and a closing `}`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use camino::{ExpandKey, ExpandOptions, Utf8Path};
+    ///
+    /// let lookup = |key: ExpandKey<'_>| match key {
+    ///     ExpandKey::HomeDir => Some("/home/alice".into()),
+    ///     ExpandKey::UserHomeDir("bob") => Some("/home/bob".into()),
+    ///     ExpandKey::Var("XDG_CACHE_HOME") => Some("/var/cache".into()),
+    ///     _ => None,
+    /// };
+    /// let options = ExpandOptions::new();
+    ///
+    /// let path = Utf8Path::new("~/.cache/foo");
+    /// assert_eq!(path.expand(options, lookup).unwrap(), "/home/alice/.cache/foo");
+    ///
+    /// let path = Utf8Path::new("~bob/.config");
+    /// assert_eq!(path.expand(options, lookup).unwrap(), "/home/bob/.config");
+    ///
+    /// let path = Utf8Path::new("${XDG_CACHE_HOME}/foo");
+    /// assert_eq!(path.expand(options, lookup).unwrap(), "/var/cache/foo");
+    ///
+    /// let error = Utf8Path::new("$CARGO_HOME/bin").expand(options, lookup).unwrap_err();
+    /// assert_eq!(error.name(), Some("CARGO_HOME"));
+    /// ```
+    pub fn expand<F>(
+        &self,
+        options: ExpandOptions,
+        mut lookup: F,
+    ) -> Result<Utf8PathBuf, ExpandError>
+    where
+        F: FnMut(ExpandKey<'_>) -> Option<OsString>,
+    {
+        let path = self.as_str();
+        let mut out = String::with_capacity(path.len());
+        let mut rest = path;
+
+        if options.tilde {
+            if let Some(after) = rest.strip_prefix('~') {
+                let end = after.find(is_separator).unwrap_or(after.len());
+                let key = match &after[..end] {
+                    "" => ExpandKey::HomeDir,
+                    user => ExpandKey::UserHomeDir(user),
+                };
+                out.push_str(&lookup_str(key, &mut lookup)?);
+                rest = &after[end..];
+            }
+        }
+
+        while let Some(index) = rest
+            .find(|c: char| (c == '$' && options.dollar_vars) || (c == '%' && options.percent_vars))
+        {
+            out.push_str(&rest[..index]);
+            // Both '$' and '%' are a single byte long.
+            let after = &rest[index + 1..];
+
+            if rest.as_bytes()[index] == b'$' {
+                if let Some(braced) = after.strip_prefix('{') {
+                    match braced.find('}') {
+                        Some(end) if end > 0 => {
+                            out.push_str(&lookup_str(ExpandKey::Var(&braced[..end]), &mut lookup)?);
+                            rest = &braced[end + 1..];
+                        }
+                        _ => {
+                            return Err(ExpandError {
+                                kind: ExpandErrorKind::InvalidBraces(
+                                    path.len() - rest.len() + index,
+                                ),
+                            });
+                        }
+                    }
+                } else {
+                    let len = dollar_var_len(after);
+                    if len > 0 {
+                        out.push_str(&lookup_str(ExpandKey::Var(&after[..len]), &mut lookup)?);
+                        rest = &after[len..];
+                    } else {
+                        out.push('$');
+                        rest = after;
+                    }
+                }
+            } else {
+                match after.find('%') {
+                    Some(end) if is_percent_var(&after[..end]) => {
+                        out.push_str(&lookup_str(ExpandKey::Var(&after[..end]), &mut lookup)?);
+                        rest = &after[end + 1..];
+                    }
+                    _ => {
+                        out.push('%');
+                        rest = after;
+                    }
+                }
+            }
+        }
+
+        out.push_str(rest);
+        Ok(out.into())
+    }
+}
+
+/// Options for [`Utf8Path::expand`], controlling which forms of expansion are performed.
+///
+/// # Examples
+///
+/// ```
+/// use camino::{ExpandKey, ExpandOptions, Utf8Path};
+///
+/// let lookup = |key: ExpandKey<'_>| match key {
+///     ExpandKey::HomeDir => Some("/home/alice".into()),
+///     _ => None,
+/// };
+///
+/// // Only expand `~`, leaving anything that looks like a variable alone.
+/// let options = ExpandOptions::new().dollar_vars(false).percent_vars(false);
+/// let path = Utf8Path::new("~/$RECYCLE.BIN/%1");
+/// assert_eq!(path.expand(options, lookup).unwrap(), "/home/alice/$RECYCLE.BIN/%1");
+/// ```
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ExpandOptions {
+    tilde: bool,
+    dollar_vars: bool,
+    percent_vars: bool,
+}
+
+impl ExpandOptions {
+    /// Creates the default set of options.
+    ///
+    /// `~`, `~user`, `$VAR` and `${VAR}` are expanded on all platforms. `%VAR%` is only expanded
+    /// on Windows, since `%` is commonly found in file names elsewhere.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            tilde: true,
+            dollar_vars: true,
+            percent_vars: cfg!(windows),
+        }
+    }
+
+    /// Sets whether a leading `~` or `~user` is expanded.
+    #[must_use]
+    pub const fn tilde(self, enabled: bool) -> Self {
+        Self {
+            tilde: enabled,
+            ..self
+        }
+    }
+
+    /// Sets whether `$VAR` and `${VAR}` are expanded.
+    #[must_use]
+    pub const fn dollar_vars(self, enabled: bool) -> Self {
+        Self {
+            dollar_vars: enabled,
+            ..self
+        }
+    }
+
+    /// Sets whether `%VAR%` is expanded.
+    #[must_use]
+    pub const fn percent_vars(self, enabled: bool) -> Self {
+        Self {
+            percent_vars: enabled,
+            ..self
+        }
+    }
+}
+
+impl Default for ExpandOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A value requested from the lookup function passed into [`Utf8Path::expand`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+#[non_exhaustive]
+pub enum ExpandKey<'a> {
+    /// The current user's home directory, requested for a leading `~`.
+    HomeDir,
+
+    /// The home directory of the given user, requested for a leading `~user`.
+    UserHomeDir(&'a str),
+
+    /// The value of the given variable, requested for `$VAR`, `${VAR}` or `%VAR%`.
+    Var(&'a str),
+}
+
+impl fmt::Display for ExpandKey<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ExpandKey::HomeDir => write!(f, "home directory"),
+            ExpandKey::UserHomeDir(user) => write!(f, "home directory for user `{user}`"),
+            ExpandKey::Var(name) => write!(f, "variable `{name}`"),
+        }
+    }
+}
+
+/// An error returned from [`Utf8Path::expand`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ExpandError {
+    kind: ExpandErrorKind,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+enum ExpandErrorKind {
+    NotFound(OwnedExpandKey),
+    NotUnicode(OwnedExpandKey, OsString),
+    InvalidBraces(usize),
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+enum OwnedExpandKey {
+    HomeDir,
+    UserHomeDir(String),
+    Var(String),
+}
+
+impl OwnedExpandKey {
+    fn as_key(&self) -> ExpandKey<'_> {
+        match self {
+            OwnedExpandKey::HomeDir => ExpandKey::HomeDir,
+            OwnedExpandKey::UserHomeDir(user) => ExpandKey::UserHomeDir(user),
+            OwnedExpandKey::Var(name) => ExpandKey::Var(name),
+        }
+    }
+}
+
+impl From<ExpandKey<'_>> for OwnedExpandKey {
+    fn from(key: ExpandKey<'_>) -> Self {
+        match key {
+            ExpandKey::HomeDir => OwnedExpandKey::HomeDir,
+            ExpandKey::UserHomeDir(user) => OwnedExpandKey::UserHomeDir(user.to_owned()),
+            ExpandKey::Var(name) => OwnedExpandKey::Var(name.to_owned()),
+        }
+    }
+}
+
+impl ExpandError {
+    /// Returns the key that couldn't be expanded, if the error was caused by a lookup.
+    #[must_use]
+    pub fn key(&self) -> Option<ExpandKey<'_>> {
+        match &self.kind {
+            ExpandErrorKind::NotFound(key) | ExpandErrorKind::NotUnicode(key, _) => {
+                Some(key.as_key())
+            }
+            ExpandErrorKind::InvalidBraces(_) => None,
+        }
+    }
+
+    /// Returns the name of the variable or user that couldn't be expanded, if any.
+    #[must_use]
+    pub fn name(&self) -> Option<&str> {
+        match self.key()? {
+            ExpandKey::HomeDir => None,
+            ExpandKey::UserHomeDir(name) | ExpandKey::Var(name) => Some(name),
+        }
+    }
+
+    /// Returns the value that was returned by the lookup function, if it wasn't valid UTF-8.
+    #[must_use]
+    pub fn not_unicode_value(&self) -> Option<&OsStr> {
+        match &self.kind {
+            ExpandErrorKind::NotUnicode(_, value) => Some(value),
+            ExpandErrorKind::NotFound(_) | ExpandErrorKind::InvalidBraces(_) => None,
+        }
+    }
+}
+
+impl fmt::Display for ExpandError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.kind {
+            ExpandErrorKind::NotFound(key) => write!(f, "{} not found", key.as_key()),
+            ExpandErrorKind::NotUnicode(key, value) => {
+                write!(f, "{} is not valid UTF-8: {value:?}", key.as_key())
+            }
+            ExpandErrorKind::InvalidBraces(index) => write!(
+                f,
+                "`${{` at byte {index} must be followed by a variable name and `}}`"
+            ),
+        }
+    }
+}
+
+impl error::Error for ExpandError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        None
+    }
+}
+
+fn lookup_str<F>(key: ExpandKey<'_>, lookup: &mut F) -> Result<String, ExpandError>
+where
+    F: FnMut(ExpandKey<'_>) -> Option<OsString>,
+{
+    match lookup(key) {
+        Some(value) => value.into_string().map_err(|value| ExpandError {
+            kind: ExpandErrorKind::NotUnicode(key.into(), value),
+        }),
+        None => Err(ExpandError {
+            kind: ExpandErrorKind::NotFound(key.into()),
+        }),
+    }
+}
+
+// Returns the length of the variable name at the start of s, for `$VAR`.
+fn dollar_var_len(s: &str) -> usize {
+    if s.starts_with(|c: char| c.is_ascii_digit()) {
+        return 0;
+    }
+    s.find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+        .unwrap_or(s.len())
+}
+
+// Returns true if s can be used as the name of a variable in `%VAR%`.
+//
+// Windows allows almost anything in variable names (e.g. `ProgramFiles(x86)`), so this only
+// rules out names that are clearly not meant to be variables.
+fn is_percent_var(s: &str) -> bool {
+    !s.is_empty() && !s.contains(|c: char| c == '=' || c.is_whitespace() || is_separator(c))
+}
@@ -47,19 +47,37 @@ use std::{
     hash::{Hash, Hasher},
     io,
     iter::FusedIterator,
-    ops::Deref,
+    ops::{Bound, Deref, Range, RangeBounds},
     path::*,
     rc::Rc,
     str::FromStr,
     sync::Arc,
 };
 
+#[macro_use]
+mod wrapper;
+
+mod anchored;
+mod expand;
+mod portable;
 #[cfg(feature = "proptest1")]
 mod proptest_impls;
+mod remap;
 #[cfg(feature = "serde1")]
 mod serde_impls;
 #[cfg(test)]
 mod tests;
+#[cfg(feature = "unicode-normalization01")]
+mod unicode_normalization_impls;
+
+pub use anchored::Utf8AnchoredPath;
+pub use expand::{ExpandError, ExpandKey, ExpandOptions};
+pub use portable::{
+    PortabilityIssue, PortabilityIssueKind, PortabilityTarget, SanitizeOptions, sanitize_file_name,
+};
+pub use remap::Utf8PathRemapper;
+#[cfg(feature = "unicode-normalization01")]
+pub use unicode_normalization_impls::Utf8PathNormalizationInsensitive;
 
 /// An owned, mutable UTF-8 path (akin to [`String`]).
 ///
@@ -219,6 +237,41 @@ impl Utf8PathBuf {
         }
     }
 
+    /// Creates a new [`Utf8PathBuf`] from a `/`-separated string, converting separators to the
+    /// platform's [`MAIN_SEPARATOR`].
+    ///
+    /// This is the inverse of [`Utf8Path::to_slash`], and is meant for reading paths that were
+    /// stored in a portable form, e.g. in lockfiles or manifests.
+    ///
+    /// Backslashes are also accepted as separators on all platforms, so that paths written out
+    /// on Windows with `\` as the separator can be read on Unix. This means that on Unix, file
+    /// names containing backslashes cannot be represented in this form.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use camino::{Utf8Path, Utf8PathBuf};
+    ///
+    /// let path = Utf8PathBuf::from_slash("src/bin/main.rs");
+    /// assert_eq!(path, Utf8Path::new("src").join("bin").join("main.rs"));
+    ///
+    /// // Backslash-separated paths are accepted on all platforms.
+    /// let path = Utf8PathBuf::from_slash(r"src\bin\main.rs");
+    /// assert_eq!(path, Utf8Path::new("src").join("bin").join("main.rs"));
+    /// ```
+    #[must_use]
+    pub fn from_slash(s: impl AsRef<str>) -> Utf8PathBuf {
+        let path: String = s
+            .as_ref()
+            .chars()
+            .map(|c| match c {
+                '/' | '\\' => MAIN_SEPARATOR,
+                c => c,
+            })
+            .collect();
+        Utf8PathBuf::from(path)
+    }
+
     /// Converts a [`Utf8PathBuf`] to a [`PathBuf`].
     ///
     /// This is equivalent to the [`From<Utf8PathBuf> for PathBuf`][from] implementation,
@@ -366,6 +419,263 @@ impl Utf8PathBuf {
         self.0.pop()
     }
 
+    /// Appends a trailing separator to `self`, if it doesn't already have one.
+    ///
+    /// The platform's [`MAIN_SEPARATOR`] is used. Does nothing if `self` is empty.
+    ///
+    /// Trailing separators are ignored by [`components`](Utf8Path::components) and path
+    /// equality, but are significant to some tools and operating system APIs, where `dir/`
+    /// refers to a directory and `dir` might refer to a file.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use camino::Utf8PathBuf;
+    ///
+    /// let mut path = Utf8PathBuf::from("/usr/lib");
+    /// path.push_trailing_separator();
+    /// assert!(path.has_trailing_separator());
+    /// # #[cfg(unix)]
+    /// assert_eq!(path.as_str(), "/usr/lib/");
+    ///
+    /// // Pushing a trailing separator again does nothing.
+    /// path.push_trailing_separator();
+    /// # #[cfg(unix)]
+    /// assert_eq!(path.as_str(), "/usr/lib/");
+    /// ```
+    pub fn push_trailing_separator(&mut self) {
+        // Pushing an empty path adds a separator if there isn't one already (and does nothing if
+        // self is empty).
+        self.0.push("")
+    }
+
+    /// Temporarily extends `self` with `path`, returning a guard that restores `self` when
+    /// dropped.
+    ///
+    /// `path` is adjoined as with [`push`](Self::push). The guard dereferences to the extended
+    /// [`Utf8PathBuf`], and when it is dropped, `self` is restored to exactly what it was before.
+    /// Unlike calling [`pop`](Self::pop) afterwards, this works for any `path`, including ones
+    /// with several components and absolute paths.
+    ///
+    /// This makes it possible to reuse a single buffer while walking a directory tree, without
+    /// having to keep `push` and `pop` calls in sync by hand.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use camino::{Utf8Path, Utf8PathBuf};
+    ///
+    /// let mut path = Utf8PathBuf::from("/srv");
+    /// {
+    ///     let mut data = path.push_scoped("data");
+    ///     assert_eq!(*data, Utf8Path::new("/srv/data"));
+    ///
+    ///     // Guards can be nested.
+    ///     let file = data.push_scoped("nested/file.txt");
+    ///     assert_eq!(*file, Utf8Path::new("/srv/data/nested/file.txt"));
+    /// }
+    /// assert_eq!(path, Utf8Path::new("/srv"));
+    /// ```
+    pub fn push_scoped(&mut self, path: impl AsRef<Utf8Path>) -> PushScopedGuard<'_> {
+        let path = path.as_ref();
+        // Pushing a path with a prefix or root replaces self, and pushing onto a verbatim path on
+        // Windows rebuilds it, resolving `..` components. Since push doesn't just append to self
+        // in these (rare) cases, the previous path is saved and restored in full.
+        let is_verbatim = matches!(
+            self.components().next(),
+            Some(Utf8Component::Prefix(prefix)) if prefix.kind().is_verbatim()
+        );
+        let replaces_or_rewrites = is_verbatim
+            || matches!(
+                path.components().next(),
+                Some(Utf8Component::Prefix(_) | Utf8Component::RootDir)
+            )
+            || path.components().any(|c| c == Utf8Component::ParentDir);
+        let previous = if replaces_or_rewrites {
+            Some(self.clone())
+        } else {
+            None
+        };
+        let len = self.as_str().len();
+        self.push(path);
+        PushScopedGuard {
+            buf: self,
+            len,
+            previous,
+        }
+    }
+
+    /// Replaces the component at `index` with `component`, in place.
+    ///
+    /// Components are counted as they are produced by [`components`](Utf8Path::components).
+    /// `component` is inserted as-is, so it can also contain several components separated by
+    /// separators.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds, if the component at `index` is a prefix or root, or if
+    /// `component` is empty or has a prefix or root.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use camino::Utf8PathBuf;
+    ///
+    /// let mut path = Utf8PathBuf::from("target/debug/deps");
+    /// path.replace_component(1, "release");
+    /// assert_eq!(path, Utf8PathBuf::from("target/release/deps"));
+    /// ```
+    pub fn replace_component(&mut self, index: usize, component: impl AsRef<str>) {
+        let ranges = self.component_ranges();
+        let range = self.editable_range(&ranges, index, "replace");
+        let component = check_inserted_component(component.as_ref());
+        self.edit_str(|s| s.replace_range(range, component));
+    }
+
+    /// Inserts `component` at position `index`, shifting all components after it, in place.
+    ///
+    /// Components are counted as they are produced by [`components`](Utf8Path::components).
+    /// `component` is inserted as-is, followed by the platform's [`MAIN_SEPARATOR`]. If `index` is
+    /// the number of components, `component` is appended to the end of the path instead, preceded
+    /// by a separator if necessary.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is greater than the number of components, if the component at `index`
+    /// is a prefix or root, or if `component` is empty or has a prefix or root.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use camino::Utf8PathBuf;
+    ///
+    /// let mut path = Utf8PathBuf::from("/usr/lib");
+    /// path.insert_component(1, "opt");
+    /// assert_eq!(path, Utf8PathBuf::from("/opt/usr/lib"));
+    ///
+    /// path.insert_component(4, "libfoo.so");
+    /// assert_eq!(path, Utf8PathBuf::from("/opt/usr/lib/libfoo.so"));
+    /// ```
+    pub fn insert_component(&mut self, index: usize, component: impl AsRef<str>) {
+        let ranges = self.component_ranges();
+        let component = check_inserted_component(component.as_ref());
+        if index == ranges.len() {
+            // A path that only consists of a prefix, such as `C:`, is followed directly by the
+            // component, since adding a separator would make the path absolute.
+            if !matches!(
+                self.components().next_back(),
+                Some(Utf8Component::Prefix(_))
+            ) {
+                self.push_trailing_separator();
+            }
+            self.edit_str(|s| s.push_str(component));
+            return;
+        }
+
+        let start = self.editable_range(&ranges, index, "insert before").start;
+        self.edit_str(|s| {
+            let mut inserted = String::with_capacity(component.len() + 1);
+            inserted.push_str(component);
+            inserted.push(MAIN_SEPARATOR);
+            s.insert_str(start, &inserted);
+        });
+    }
+
+    /// Removes the component at `index`, in place.
+    ///
+    /// Components are counted as they are produced by [`components`](Utf8Path::components).
+    /// Separators following the component are removed along with it. Removing the root makes the
+    /// path relative.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use camino::Utf8PathBuf;
+    ///
+    /// let mut path = Utf8PathBuf::from("target/x86_64-unknown-linux-gnu/debug");
+    /// path.remove_component(1);
+    /// assert_eq!(path, Utf8PathBuf::from("target/debug"));
+    ///
+    /// path.remove_component(1);
+    /// assert_eq!(path, Utf8PathBuf::from("target"));
+    /// ```
+    pub fn remove_component(&mut self, index: usize) {
+        let ranges = self.component_ranges();
+        assert!(
+            index < ranges.len(),
+            "component index {index} out of range for path with {} components",
+            ranges.len(),
+        );
+        self.edit_str(|s| remove_component_range(s, &ranges, index, index + 1 == ranges.len()));
+    }
+
+    /// Retains only the components for which `f` returns `true`, in place.
+    ///
+    /// Components are visited in order, as they are produced by
+    /// [`components`](Utf8Path::components). Removed components are removed along with the
+    /// separators that follow them, as with [`remove_component`](Self::remove_component).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use camino::{Utf8Component, Utf8PathBuf};
+    ///
+    /// let mut path = Utf8PathBuf::from("./src/./generated/lib.rs");
+    /// path.retain_components(|c| c != Utf8Component::CurDir && c.as_str() != "generated");
+    /// assert_eq!(path, Utf8PathBuf::from("src/lib.rs"));
+    /// ```
+    pub fn retain_components<F>(&mut self, mut f: F)
+    where
+        F: FnMut(Utf8Component<'_>) -> bool,
+    {
+        let keep: Vec<bool> = self.components().map(&mut f).collect();
+        if keep.iter().all(|&keep| keep) {
+            return;
+        }
+
+        let ranges = self.component_ranges();
+        self.edit_str(|s| {
+            // Remove components from the end, so that the ranges of earlier components stay valid.
+            // A component is the last one if every component after it has been removed.
+            let mut is_last = true;
+            for (index, &keep) in keep.iter().enumerate().rev() {
+                if keep {
+                    is_last = false;
+                } else {
+                    remove_component_range(s, &ranges, index, is_last);
+                }
+            }
+        });
+    }
+
+    // Returns the range of the component at index, which must not be a prefix or root.
+    fn editable_range(&self, ranges: &[Range<usize>], index: usize, action: &str) -> Range<usize> {
+        let range = match ranges.get(index) {
+            Some(range) => range.clone(),
+            None => panic!(
+                "component index {index} out of range for path with {} components",
+                ranges.len(),
+            ),
+        };
+        if let Some(Utf8Component::Prefix(_) | Utf8Component::RootDir) =
+            self.components().nth(index)
+        {
+            panic!("cannot {action} the prefix or root of {self}");
+        }
+        range
+    }
+
+    // Edits the underlying string in place, reusing its buffer.
+    fn edit_str(&mut self, f: impl FnOnce(&mut String)) {
+        let mut s = std::mem::take(self).into_string();
+        f(&mut s);
+        *self = Utf8PathBuf::from(s);
+    }
+
     /// Updates [`self.file_name`] to `file_name`.
     ///
     /// If [`self.file_name`] was [`None`], this is equivalent to pushing
@@ -625,6 +935,120 @@ impl<P: AsRef<Utf8Path>> Extend<P> for Utf8PathBuf {
     }
 }
 
+/// Joins a path onto this one, reusing the buffer. Equivalent to [`Utf8PathBuf::push`].
+///
+/// # Examples
+///
+/// ```
+/// use camino::{Utf8Path, Utf8PathBuf};
+///
+/// let root = Utf8PathBuf::from("/project");
+/// let name = "main.rs";
+/// assert_eq!(root / "src" / name, Utf8Path::new("/project/src/main.rs"));
+/// ```
+impl<P: AsRef<Utf8Path>> std::ops::Div<P> for Utf8PathBuf {
+    type Output = Utf8PathBuf;
+
+    #[inline]
+    fn div(mut self, path: P) -> Utf8PathBuf {
+        self.push(path);
+        self
+    }
+}
+
+/// Joins a path onto this one, returning a new [`Utf8PathBuf`]. Equivalent to
+/// [`Utf8Path::join`].
+impl<P: AsRef<Utf8Path>> std::ops::Div<P> for &Utf8PathBuf {
+    type Output = Utf8PathBuf;
+
+    #[inline]
+    fn div(self, path: P) -> Utf8PathBuf {
+        self.join(path)
+    }
+}
+
+/// Appends a path to this one. Equivalent to [`Utf8PathBuf::push`].
+///
+/// # Examples
+///
+/// ```
+/// use camino::{Utf8Path, Utf8PathBuf};
+///
+/// let mut path = Utf8PathBuf::from("target");
+/// path /= "debug";
+/// assert_eq!(path, Utf8Path::new("target/debug"));
+/// ```
+impl<P: AsRef<Utf8Path>> std::ops::DivAssign<P> for Utf8PathBuf {
+    #[inline]
+    fn div_assign(&mut self, path: P) {
+        self.push(path);
+    }
+}
+
+/// A guard that restores a [`Utf8PathBuf`] to its previous contents when dropped.
+///
+/// This `struct` is created by the [`push_scoped`] method on [`Utf8PathBuf`].
+/// See its documentation for more.
+///
+/// The guard only gives shared access to the extended path, so that it is always restored to
+/// exactly its previous contents. To extend the path further, call
+/// [`push_scoped`](Self::push_scoped) on the guard.
+///
+/// If the pushed path has a root or prefix, it replaces the path rather than extending it, and on
+/// Windows, pushing onto a verbatim path (`\\?\...`) resolves `..` components. In these cases,
+/// and whenever the pushed path contains `..`, the guard holds on to a copy of the previous path,
+/// which it restores when dropped.
+///
+/// [`push_scoped`]: Utf8PathBuf::push_scoped
+#[must_use = "if unused, the path will immediately be restored"]
+pub struct PushScopedGuard<'a> {
+    buf: &'a mut Utf8PathBuf,
+    len: usize,
+    previous: Option<Utf8PathBuf>,
+}
+
+impl Deref for PushScopedGuard<'_> {
+    type Target = Utf8PathBuf;
+
+    fn deref(&self) -> &Utf8PathBuf {
+        self.buf
+    }
+}
+
+impl PushScopedGuard<'_> {
+    /// Temporarily extends the path further, returning a nested guard.
+    ///
+    /// This is the same as [`Utf8PathBuf::push_scoped`]: when the nested guard is dropped, the
+    /// path is restored to what it was before this call.
+    pub fn push_scoped(&mut self, path: impl AsRef<Utf8Path>) -> PushScopedGuard<'_> {
+        self.buf.push_scoped(path)
+    }
+}
+
+impl fmt::Debug for PushScopedGuard<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("PushScopedGuard")
+            .field("path", &self.buf)
+            .field("len", &self.len)
+            .finish()
+    }
+}
+
+impl Drop for PushScopedGuard<'_> {
+    fn drop(&mut self) {
+        if let Some(previous) = self.previous.take() {
+            *self.buf = previous;
+            return;
+        }
+        // The path can only have been extended through nested guards, which have already been
+        // dropped. The check guards against nested guards that were leaked with mem::forget.
+        let len = self.len;
+        if self.buf.as_str().is_char_boundary(len) {
+            self.buf.edit_str(|s| s.truncate(len));
+        }
+    }
+}
+
 /// A slice of a UTF-8 path (akin to [`str`]).
 ///
 /// This type supports a number of operations for inspecting a path, including
@@ -814,6 +1238,41 @@ impl Utf8Path {
         self.0.as_os_str()
     }
 
+    /// Returns the path as a string with `/` as the separator on all platforms.
+    ///
+    /// This is useful for storing paths in a portable form that is the same across platforms,
+    /// e.g. in lockfiles, snapshots or cache keys. Use [`Utf8PathBuf::from_slash`] to convert the
+    /// result back into a platform-specific path.
+    ///
+    /// On platforms where `/` is the only separator, such as Unix, this returns the path unchanged.
+    /// Note that on Unix, backslashes are not separators and are left as-is.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use camino::Utf8Path;
+    ///
+    /// let path = Utf8Path::new("src").join("bin").join("main.rs");
+    /// assert_eq!(path.to_slash(), "src/bin/main.rs");
+    ///
+    /// # if cfg!(windows) {
+    /// assert_eq!(Utf8Path::new(r"C:\Users\Rust").to_slash(), "C:/Users/Rust");
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn to_slash(&self) -> Cow<'_, str> {
+        let s = self.as_str();
+        if s.chars().any(|c| c != '/' && is_separator(c)) {
+            Cow::Owned(
+                s.chars()
+                    .map(|c| if is_separator(c) { '/' } else { c })
+                    .collect(),
+            )
+        } else {
+            Cow::Borrowed(s)
+        }
+    }
+
     /// Converts a [`Utf8Path`] to an owned [`Utf8PathBuf`].
     ///
     /// # Examples
@@ -896,6 +1355,45 @@ impl Utf8Path {
         self.0.has_root()
     }
 
+    /// Returns the kind of the path, classifying it according to its prefix and root.
+    ///
+    /// See [`Utf8PathKind`] for the possible kinds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use camino::{Utf8Path, Utf8PathKind};
+    ///
+    /// assert_eq!(Utf8Path::new("foo.txt").kind(), Utf8PathKind::Relative);
+    /// # #[cfg(unix)]
+    /// assert_eq!(Utf8Path::new("/etc/passwd").kind(), Utf8PathKind::Absolute);
+    /// # #[cfg(windows)]
+    /// assert_eq!(Utf8Path::new(r"C:foo.txt").kind(), Utf8PathKind::DriveRelative);
+    /// ```
+    #[must_use]
+    pub fn kind(&self) -> Utf8PathKind {
+        match self.components().next() {
+            Some(Utf8Component::Prefix(prefix)) => match prefix.kind() {
+                Utf8Prefix::Verbatim(_)
+                | Utf8Prefix::VerbatimUNC(..)
+                | Utf8Prefix::VerbatimDisk(_) => Utf8PathKind::Verbatim,
+                Utf8Prefix::DeviceNS(_) => Utf8PathKind::Device,
+                Utf8Prefix::UNC(..) => Utf8PathKind::Unc,
+                Utf8Prefix::Disk(_) => {
+                    if self.has_root() {
+                        Utf8PathKind::Absolute
+                    } else {
+                        Utf8PathKind::DriveRelative
+                    }
+                }
+            },
+            // A root without a prefix can only be relative to the current drive on Windows.
+            Some(Utf8Component::RootDir) if cfg!(windows) => Utf8PathKind::RootRelative,
+            Some(Utf8Component::RootDir) => Utf8PathKind::Absolute,
+            _ => Utf8PathKind::Relative,
+        }
+    }
+
     /// Returns the [`Path`] without its final component, if there is one.
     ///
     /// Returns [`None`] if the path terminates in a root or prefix.
@@ -955,6 +1453,31 @@ impl Utf8Path {
         Utf8Ancestors(self.0.ancestors())
     }
 
+    /// Returns the `n`th ancestor of the path, if there is one.
+    ///
+    /// `nth_ancestor(0)` is the path itself, `nth_ancestor(1)` is its [`parent`], and so on. This
+    /// is equivalent to `self.ancestors().nth(n)`, and returns a slice of `self` without
+    /// allocating.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use camino::Utf8Path;
+    ///
+    /// let path = Utf8Path::new("/foo/bar/baz");
+    /// assert_eq!(path.nth_ancestor(0), Some(path));
+    /// assert_eq!(path.nth_ancestor(2), Some(Utf8Path::new("/foo")));
+    /// assert_eq!(path.nth_ancestor(3), Some(Utf8Path::new("/")));
+    /// assert_eq!(path.nth_ancestor(4), None);
+    /// ```
+    ///
+    /// [`parent`]: Utf8Path::parent
+    #[inline]
+    #[must_use]
+    pub fn nth_ancestor(&self, n: usize) -> Option<&Utf8Path> {
+        self.ancestors().nth(n)
+    }
+
     /// Returns the final component of the [`Utf8Path`], if there is one.
     ///
     /// If the path is a normal file, this is the file name. If it's the path of a directory, this
@@ -1020,73 +1543,351 @@ impl Utf8Path {
         })
     }
 
-    /// Determines whether `base` is a prefix of `self`.
+    /// Returns the portion of the path that precedes `child`, if `child` is a suffix of `self`.
     ///
-    /// Only considers whole path components to match.
+    /// This is the counterpart to [`strip_prefix`]: it returns [`Some`] if and only if
+    /// [`ends_with`] returns `true`, and the result is a slice of `self`.
+    ///
+    /// [`strip_prefix`]: Utf8Path::strip_prefix
+    /// [`ends_with`]: Utf8Path::ends_with
     ///
     /// # Examples
     ///
     /// ```
     /// use camino::Utf8Path;
     ///
-    /// let path = Utf8Path::new("/etc/passwd");
-    ///
-    /// assert!(path.starts_with("/etc"));
-    /// assert!(path.starts_with("/etc/"));
-    /// assert!(path.starts_with("/etc/passwd"));
-    /// assert!(path.starts_with("/etc/passwd/")); // extra slash is okay
-    /// assert!(path.starts_with("/etc/passwd///")); // multiple extra slashes are okay
+    /// let path = Utf8Path::new("/etc/resolv.conf");
     ///
-    /// assert!(!path.starts_with("/e"));
-    /// assert!(!path.starts_with("/etc/passwd.txt"));
+    /// assert_eq!(path.strip_suffix("resolv.conf"), Some(Utf8Path::new("/etc")));
+    /// assert_eq!(path.strip_suffix("etc/resolv.conf"), Some(Utf8Path::new("/")));
+    /// assert_eq!(path.strip_suffix("/etc/resolv.conf"), Some(Utf8Path::new("")));
     ///
-    /// assert!(!Utf8Path::new("/etc/foo.rs").starts_with("/etc/foo"));
+    /// assert_eq!(path.strip_suffix("conf"), None);
+    /// assert_eq!(path.strip_suffix("/resolv.conf"), None);
     /// ```
-    #[inline]
     #[must_use]
-    pub fn starts_with(&self, base: impl AsRef<Path>) -> bool {
-        self.0.starts_with(base)
+    pub fn strip_suffix(&self, child: impl AsRef<Path>) -> Option<&Utf8Path> {
+        let mut components = self.0.components();
+        for expected in child.as_ref().components().rev() {
+            match components.next_back() {
+                Some(actual) if actual == expected => {}
+                _ => return None,
+            }
+        }
+        // SAFETY: self is valid UTF-8, and the remaining components are a part of self, so they
+        // are valid UTF-8 as well.
+        Some(unsafe { Utf8Path::assume_utf8(components.as_path()) })
     }
 
-    /// Determines whether `child` is a suffix of `self`.
+    /// Returns a relative path that, when joined onto `base`, yields a path equivalent to `self`.
     ///
-    /// Only considers whole path components to match.
+    /// Unlike [`strip_prefix`], `self` does not need to be a descendant of `base`: each
+    /// component of `base` that is not shared with `self` is turned into a `..` component.
+    ///
+    /// This is a purely lexical operation, and does not access the filesystem or resolve
+    /// symlinks. In most cases, both paths should be [normalized](Self::normalize_lexically)
+    /// first. If `self` and `base` are the same path, the result is the empty path.
+    ///
+    /// Returns [`None`] if no such relative path can be determined lexically:
+    ///
+    /// * if one of the paths is absolute and the other is relative, or if they have different
+    ///   roots or prefixes (e.g. `C:\` and `D:\` on Windows);
+    /// * if the part of `base` not shared with `self` contains `..` components.
+    ///
+    /// [`strip_prefix`]: Utf8Path::strip_prefix
     ///
     /// # Examples
     ///
     /// ```
     /// use camino::Utf8Path;
     ///
-    /// let path = Utf8Path::new("/etc/resolv.conf");
+    /// let path = Utf8Path::new("/ws/lib/foo.rs");
     ///
-    /// assert!(path.ends_with("resolv.conf"));
-    /// assert!(path.ends_with("etc/resolv.conf"));
-    /// assert!(path.ends_with("/etc/resolv.conf"));
+    /// assert_eq!(path.relative_to("/ws").unwrap(), "lib/foo.rs");
+    /// assert_eq!(path.relative_to("/ws/target/debug").unwrap(), "../../lib/foo.rs");
+    /// assert_eq!(path.relative_to("/ws/lib/foo.rs").unwrap(), "");
+    /// assert_eq!(Utf8Path::new("/ws").relative_to(path).unwrap(), "../..");
     ///
-    /// assert!(!path.ends_with("/resolv.conf"));
-    /// assert!(!path.ends_with("conf")); // use .extension() instead
+    /// assert_eq!(Utf8Path::new("src/main.rs").relative_to("tests").unwrap(), "../src/main.rs");
+    ///
+    /// // Absolute and relative paths cannot be related lexically.
+    /// assert_eq!(path.relative_to("ws"), None);
+    /// // Neither can a base with unresolved `..` components.
+    /// assert_eq!(path.relative_to("/ws/target/.."), None);
     /// ```
-    #[inline]
     #[must_use]
-    pub fn ends_with(&self, base: impl AsRef<Path>) -> bool {
-        self.0.ends_with(base)
+    pub fn relative_to(&self, base: impl AsRef<Utf8Path>) -> Option<Utf8PathBuf> {
+        let mut path_components = self
+            .components()
+            .filter(|c| *c != Utf8Component::CurDir)
+            .peekable();
+        let mut base_components = base
+            .as_ref()
+            .components()
+            .filter(|c| *c != Utf8Component::CurDir)
+            .peekable();
+
+        // Skip over the components shared by both paths.
+        while let (Some(a), Some(b)) = (path_components.peek(), base_components.peek()) {
+            if a != b {
+                break;
+            }
+            path_components.next();
+            base_components.next();
+        }
+
+        let mut relative = Utf8PathBuf::new();
+        for component in base_components {
+            match component {
+                Utf8Component::Normal(_) => relative.push(".."),
+                // A `..` in base can't be undone without knowing what it refers to, and
+                // differing roots or prefixes can't be bridged by a relative path.
+                Utf8Component::ParentDir | Utf8Component::Prefix(_) | Utf8Component::RootDir => {
+                    return None;
+                }
+                Utf8Component::CurDir => unreachable!("filtered out above"),
+            }
+        }
+        for component in path_components {
+            match component {
+                Utf8Component::Prefix(_) | Utf8Component::RootDir => return None,
+                _ => relative.push(component),
+            }
+        }
+
+        Some(relative)
     }
 
-    /// Extracts the stem (non-extension) portion of [`self.file_name`].
+    /// Replaces the prefix `from` of the path with `to`.
     ///
-    /// [`self.file_name`]: Utf8Path::file_name
+    /// This is equivalent to stripping `from` with [`strip_prefix`] and [joining](Self::join) the
+    /// rest onto `to`, except that if `self` and `from` are equal, `to` is returned as-is rather
+    /// than with a trailing separator.
     ///
-    /// The stem is:
+    /// [`strip_prefix`]: Utf8Path::strip_prefix
     ///
-    /// * [`None`], if there is no file name;
-    /// * The entire file name if there is no embedded `.`;
-    /// * The entire file name if the file name begins with `.` and has no other `.`s within;
-    /// * Otherwise, the portion of the file name before the final `.`
+    /// # Errors
+    ///
+    /// If `from` is not a prefix of `self` (i.e., [`starts_with`] returns `false`), returns
+    /// [`Err`].
+    ///
+    /// [`starts_with`]: Utf8Path::starts_with
     ///
     /// # Examples
     ///
     /// ```
-    /// use camino::Utf8Path;
+    /// use camino::{Utf8Path, Utf8PathBuf};
+    ///
+    /// let path = Utf8Path::new("src/a/b.rs");
+    /// assert_eq!(path.rebase("src", "out"), Ok(Utf8PathBuf::from("out/a/b.rs")));
+    /// assert_eq!(path.rebase("src/a", "/tmp"), Ok(Utf8PathBuf::from("/tmp/b.rs")));
+    ///
+    /// assert_eq!(path.rebase("src/a/b.rs", "out/c.rs").unwrap().as_str(), "out/c.rs");
+    ///
+    /// assert!(path.rebase("out", "src").is_err());
+    /// ```
+    pub fn rebase(
+        &self,
+        from: impl AsRef<Path>,
+        to: impl AsRef<Utf8Path>,
+    ) -> Result<Utf8PathBuf, StripPrefixError> {
+        let rest = self.strip_prefix(from)?;
+        if rest.as_str().is_empty() {
+            // Joining an empty path would add a trailing separator.
+            Ok(to.as_ref().to_path_buf())
+        } else {
+            Ok(to.as_ref().join(rest))
+        }
+    }
+
+    /// Moves the path underneath `sysroot`, keeping absolute paths within it.
+    ///
+    /// Unlike [`join`](Self::join), where an absolute path replaces the base, this removes the
+    /// root (and on Windows, the prefix) from `self` before joining the rest onto `sysroot`. This
+    /// is useful to map a path on the host system into a chroot or a sysroot for
+    /// cross-compilation.
+    ///
+    /// Relative paths are joined onto `sysroot` as-is. No normalization is performed, so a path
+    /// containing `..` may point outside of `sysroot`; use [`join_within`](Self::join_within) to
+    /// rule this out.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use camino::{Utf8Path, Utf8PathBuf};
+    ///
+    /// let sysroot = Utf8Path::new("/sysroot");
+    ///
+    /// assert_eq!(
+    ///     Utf8Path::new("/usr/lib/x").reroot(sysroot),
+    ///     Utf8PathBuf::from("/sysroot/usr/lib/x"),
+    /// );
+    /// assert_eq!(Utf8Path::new("lib/x").reroot(sysroot), Utf8PathBuf::from("/sysroot/lib/x"));
+    /// assert_eq!(Utf8Path::new("/").reroot(sysroot), Utf8PathBuf::from("/sysroot"));
+    ///
+    /// // Compare with join, which replaces the sysroot:
+    /// assert_eq!(sysroot.join("/usr/lib/x"), Utf8PathBuf::from("/usr/lib/x"));
+    /// ```
+    #[must_use]
+    pub fn reroot(&self, sysroot: impl AsRef<Utf8Path>) -> Utf8PathBuf {
+        let mut components = self.components();
+        while let Some(Utf8Component::Prefix(_) | Utf8Component::RootDir) =
+            components.clone().next()
+        {
+            components.next();
+        }
+
+        let rest = components.as_path();
+        if rest.as_str().is_empty() {
+            // Joining an empty path would add a trailing separator.
+            sysroot.as_ref().to_path_buf()
+        } else {
+            sysroot.as_ref().join(rest)
+        }
+    }
+
+    /// Determines whether `base` is a prefix of `self`.
+    ///
+    /// Only considers whole path components to match.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use camino::Utf8Path;
+    ///
+    /// let path = Utf8Path::new("/etc/passwd");
+    ///
+    /// assert!(path.starts_with("/etc"));
+    /// assert!(path.starts_with("/etc/"));
+    /// assert!(path.starts_with("/etc/passwd"));
+    /// assert!(path.starts_with("/etc/passwd/")); // extra slash is okay
+    /// assert!(path.starts_with("/etc/passwd///")); // multiple extra slashes are okay
+    ///
+    /// assert!(!path.starts_with("/e"));
+    /// assert!(!path.starts_with("/etc/passwd.txt"));
+    ///
+    /// assert!(!Utf8Path::new("/etc/foo.rs").starts_with("/etc/foo"));
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn starts_with(&self, base: impl AsRef<Path>) -> bool {
+        self.0.starts_with(base)
+    }
+
+    /// Determines whether `child` is a suffix of `self`.
+    ///
+    /// Only considers whole path components to match.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use camino::Utf8Path;
+    ///
+    /// let path = Utf8Path::new("/etc/resolv.conf");
+    ///
+    /// assert!(path.ends_with("resolv.conf"));
+    /// assert!(path.ends_with("etc/resolv.conf"));
+    /// assert!(path.ends_with("/etc/resolv.conf"));
+    ///
+    /// assert!(!path.ends_with("/resolv.conf"));
+    /// assert!(!path.ends_with("conf")); // use .extension() instead
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn ends_with(&self, base: impl AsRef<Path>) -> bool {
+        self.0.ends_with(base)
+    }
+
+    /// Returns `true` if the path ends with a separator, e.g. `dir/`.
+    ///
+    /// Trailing separators are ignored by [`components`](Self::components) and path equality, but
+    /// are significant to some tools and operating system APIs, where `dir/` refers to a directory
+    /// and `dir` might refer to a file.
+    ///
+    /// Paths consisting of just a root, such as `/`, are considered to have a trailing separator.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use camino::Utf8Path;
+    ///
+    /// assert!(Utf8Path::new("dir/").has_trailing_separator());
+    /// assert!(Utf8Path::new("/").has_trailing_separator());
+    ///
+    /// assert!(!Utf8Path::new("dir").has_trailing_separator());
+    /// assert!(!Utf8Path::new("dir/.").has_trailing_separator());
+    /// assert!(!Utf8Path::new("").has_trailing_separator());
+    /// ```
+    #[must_use]
+    pub fn has_trailing_separator(&self) -> bool {
+        self.as_str()
+            .chars()
+            .next_back()
+            .map_or(false, is_separator)
+    }
+
+    /// Returns `self` with any trailing separators removed.
+    ///
+    /// If `self` consists of just a root (such as `/`, or `C:\` on Windows), it is returned
+    /// unchanged, since removing the separator would change the meaning of the path.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use camino::Utf8Path;
+    ///
+    /// assert_eq!(Utf8Path::new("dir/").trim_trailing_separators().as_str(), "dir");
+    /// assert_eq!(Utf8Path::new("/usr/lib///").trim_trailing_separators().as_str(), "/usr/lib");
+    /// assert_eq!(Utf8Path::new("dir").trim_trailing_separators().as_str(), "dir");
+    /// assert_eq!(Utf8Path::new("/").trim_trailing_separators().as_str(), "/");
+    /// ```
+    #[must_use]
+    pub fn trim_trailing_separators(&self) -> &Utf8Path {
+        if self.has_trailing_separator() && (!self.has_root() || self.parent().is_some()) {
+            Utf8Path::new(self.as_str().trim_end_matches(is_separator))
+        } else {
+            self
+        }
+    }
+
+    /// Compares `self` and `other` for equality, also requiring that either both or neither of
+    /// them have a trailing separator.
+    ///
+    /// The [`PartialEq`] implementation for [`Utf8Path`] compares paths by their
+    /// [`components`](Self::components), which ignore trailing separators. This method also checks
+    /// [`has_trailing_separator`](Self::has_trailing_separator).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use camino::Utf8Path;
+    ///
+    /// let path = Utf8Path::new("/usr/lib/");
+    /// assert_eq!(path, Utf8Path::new("/usr/lib"));
+    /// assert!(!path.eq_including_trailing_separator("/usr/lib"));
+    /// assert!(path.eq_including_trailing_separator("/usr//lib/"));
+    /// ```
+    #[must_use]
+    pub fn eq_including_trailing_separator(&self, other: impl AsRef<Utf8Path>) -> bool {
+        let other = other.as_ref();
+        self == other && self.has_trailing_separator() == other.has_trailing_separator()
+    }
+
+    /// Extracts the stem (non-extension) portion of [`self.file_name`].
+    ///
+    /// [`self.file_name`]: Utf8Path::file_name
+    ///
+    /// The stem is:
+    ///
+    /// * [`None`], if there is no file name;
+    /// * The entire file name if there is no embedded `.`;
+    /// * The entire file name if the file name begins with `.` and has no other `.`s within;
+    /// * Otherwise, the portion of the file name before the final `.`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use camino::Utf8Path;
     ///
     /// assert_eq!("foo", Utf8Path::new("foo.rs").file_stem().unwrap());
     /// assert_eq!("foo.tar", Utf8Path::new("foo.tar.gz").file_stem().unwrap());
@@ -1161,6 +1962,120 @@ impl Utf8Path {
         })
     }
 
+    /// Produces an iterator over all the extensions of [`self.file_name`].
+    ///
+    /// While [`extension`] only returns the portion of the file name after the final `.`, this
+    /// returns each `.`-separated part after the [file prefix]. The iterator is empty if:
+    ///
+    /// * there is no file name;
+    /// * there is no embedded `.`;
+    /// * the file name begins with `.` and has no other `.`s within.
+    ///
+    /// [`self.file_name`]: Utf8Path::file_name
+    /// [`extension`]: Utf8Path::extension
+    /// [file prefix]: Utf8Path::strip_all_extensions
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use camino::Utf8Path;
+    ///
+    /// let extensions: Vec<_> = Utf8Path::new("foo.tar.gz").extensions().collect();
+    /// assert_eq!(extensions, ["tar", "gz"]);
+    ///
+    /// let extensions: Vec<_> = Utf8Path::new(".eslintrc.json").extensions().collect();
+    /// assert_eq!(extensions, ["json"]);
+    ///
+    /// assert_eq!(Utf8Path::new(".bashrc").extensions().next(), None);
+    /// assert_eq!(Utf8Path::new("Makefile").extensions().next(), None);
+    /// ```
+    pub fn extensions(&self) -> Extensions<'_> {
+        Extensions {
+            inner: self
+                .file_name()
+                .and_then(|name| split_file_at_dot(name).1)
+                .map(|extensions| extensions.split('.')),
+        }
+    }
+
+    /// Returns `true` if [`self.file_name`] ends with the given extension, ignoring case.
+    ///
+    /// `extension` may consist of several `.`-separated parts, such as `tar.gz`, in which case
+    /// all of them must match. It should not include a leading `.`.
+    ///
+    /// Extensions are compared using Unicode simple case folding, as with
+    /// [`eq_ignore_case`](Self::eq_ignore_case).
+    ///
+    /// [`self.file_name`]: Utf8Path::file_name
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use camino::Utf8Path;
+    ///
+    /// let path = Utf8Path::new("archive.TAR.GZ");
+    /// assert!(path.has_extension("gz"));
+    /// assert!(path.has_extension("tar.gz"));
+    /// assert!(!path.has_extension("tar"));
+    /// assert!(!path.has_extension("ar.gz"));
+    ///
+    /// // The leading `.` of a dotfile doesn't start an extension.
+    /// assert!(!Utf8Path::new(".gz").has_extension("gz"));
+    /// ```
+    #[must_use]
+    pub fn has_extension(&self, extension: impl AsRef<str>) -> bool {
+        let extensions = match self.file_name().and_then(|name| split_file_at_dot(name).1) {
+            Some(extensions) => extensions,
+            None => return false,
+        };
+
+        // Compare from the end, so that "gz" and "tar.gz" both match "tar.gz".
+        let mut actual = extensions.chars().rev();
+        for expected in extension.as_ref().chars().rev() {
+            match actual.next() {
+                Some(c) if simple_case_fold(c) == simple_case_fold(expected) => {}
+                _ => return false,
+            }
+        }
+        matches!(actual.next(), None | Some('.'))
+    }
+
+    /// Returns `self` with all extensions removed from [`self.file_name`].
+    ///
+    /// The part of the file name that is kept is the portion before the first `.` that isn't at
+    /// the beginning of the file name. If there are no extensions, `self` is returned unchanged.
+    ///
+    /// This does not allocate, since the result is always a prefix of `self`.
+    ///
+    /// [`self.file_name`]: Utf8Path::file_name
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use camino::Utf8Path;
+    ///
+    /// assert_eq!(Utf8Path::new("dist/foo.tar.gz").strip_all_extensions(), "dist/foo");
+    /// assert_eq!(Utf8Path::new("types/index.d.ts").strip_all_extensions(), "types/index");
+    /// assert_eq!(Utf8Path::new(".config.toml").strip_all_extensions(), ".config");
+    /// assert_eq!(Utf8Path::new("src/main").strip_all_extensions(), "src/main");
+    /// ```
+    #[must_use]
+    pub fn strip_all_extensions(&self) -> &Utf8Path {
+        let name = match self.file_name() {
+            Some(name) => name,
+            None => return self,
+        };
+        match split_file_at_dot(name) {
+            (prefix, Some(_)) => {
+                // file_name returns a slice of self, so the offset of the prefix within self can be
+                // computed from the pointers.
+                let start = prefix.as_ptr() as usize - self.as_str().as_ptr() as usize;
+                Utf8Path::new(&self.as_str()[..start + prefix.len()])
+            }
+            (_, None) => self,
+        }
+    }
+
     /// Creates an owned [`Utf8PathBuf`] with `path` adjoined to `self`.
     ///
     /// See [`Utf8PathBuf::push`] for more details on what it means to adjoin a path.
@@ -1178,6 +2093,30 @@ impl Utf8Path {
         Utf8PathBuf(self.0.join(&path.as_ref().0))
     }
 
+    /// Writes `self` with `path` adjoined into `buf`, reusing its allocation.
+    ///
+    /// The previous contents of `buf` are discarded. The result is the same as that of
+    /// [`join`](Self::join), but if `buf` already has enough capacity, no allocation is made.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use camino::{Utf8Path, Utf8PathBuf};
+    ///
+    /// let root = Utf8Path::new("/srv/data");
+    /// let mut buf = Utf8PathBuf::with_capacity(64);
+    ///
+    /// for name in ["a.txt", "b.txt"] {
+    ///     root.join_into(name, &mut buf);
+    ///     assert_eq!(buf, root.join(name));
+    /// }
+    /// ```
+    pub fn join_into(&self, path: impl AsRef<Utf8Path>, buf: &mut Utf8PathBuf) {
+        buf.clear();
+        buf.push(self);
+        buf.push(path);
+    }
+
     /// Creates an owned [`PathBuf`] with `path` adjoined to `self`.
     ///
     /// See [`PathBuf::push`] for more details on what it means to adjoin a path.
@@ -1196,6 +2135,72 @@ impl Utf8Path {
         self.0.join(path)
     }
 
+    /// Creates an owned [`Utf8PathBuf`] with `path` adjoined to `self`, ensuring that the result
+    /// stays within `self`.
+    ///
+    /// Unlike [`join`](Self::join), where an absolute `path` replaces `self` entirely, this is
+    /// meant for joining untrusted input (e.g. entries in an archive, or URL paths) onto a root
+    /// directory. `.` and `..` components in `path` are resolved lexically, and `..` is allowed
+    /// as long as it doesn't climb above `self`.
+    ///
+    /// This is a purely lexical check and does not access the filesystem. In particular, it does
+    /// not protect against symlinks within `self` that point outside of it.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`JoinWithinError`] if `path`:
+    ///
+    /// * has a root, e.g. `/etc/passwd`, or `\Windows` on Windows;
+    /// * has a prefix, e.g. `C:foo` or `\\server\share` on Windows;
+    /// * contains a `..` component that would climb above `self`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use camino::Utf8Path;
+    ///
+    /// let root = Utf8Path::new("/srv/www");
+    ///
+    /// assert_eq!(root.join_within("index.html").unwrap(), "/srv/www/index.html");
+    /// assert_eq!(root.join_within("css/../img/./logo.png").unwrap(), "/srv/www/img/logo.png");
+    ///
+    /// assert!(root.join_within("/etc/passwd").is_err());
+    /// assert!(root.join_within("../../etc/passwd").is_err());
+    /// assert!(root.join_within("img/../../www2").is_err());
+    /// ```
+    pub fn join_within(&self, path: impl AsRef<Utf8Path>) -> Result<Utf8PathBuf, JoinWithinError> {
+        let path = path.as_ref();
+        let mut joined = self.to_path_buf();
+        // The number of components pushed onto joined, i.e. the number of `..` components that
+        // can be resolved without escaping self.
+        let mut depth = 0_usize;
+
+        for component in path.components() {
+            let kind = match component {
+                Utf8Component::Prefix(_) => JoinWithinErrorKind::HasPrefix,
+                Utf8Component::RootDir => JoinWithinErrorKind::HasRoot,
+                Utf8Component::CurDir => continue,
+                Utf8Component::ParentDir if depth > 0 => {
+                    joined.pop();
+                    depth -= 1;
+                    continue;
+                }
+                Utf8Component::ParentDir => JoinWithinErrorKind::EscapesRoot,
+                Utf8Component::Normal(name) => {
+                    joined.push(name);
+                    depth += 1;
+                    continue;
+                }
+            };
+            return Err(JoinWithinError {
+                path: path.to_path_buf(),
+                kind,
+            });
+        }
+
+        Ok(joined)
+    }
+
     /// Creates an owned [`Utf8PathBuf`] like `self` but with the given file name.
     ///
     /// See [`Utf8PathBuf::set_file_name`] for more details.
@@ -1264,6 +2269,75 @@ impl Utf8Path {
         Utf8PathBuf(self.0.with_added_extension(extension.as_ref()))
     }
 
+    /// Creates an owned [`Utf8PathBuf`] like `self` but with the given file stem.
+    ///
+    /// The [`extension`] (the portion of the file name after the final `.`), if any, is kept. If
+    /// [`self.file_name`] is [`None`], this returns `self` unchanged.
+    ///
+    /// [`extension`]: Utf8Path::extension
+    /// [`self.file_name`]: Utf8Path::file_name
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use camino::{Utf8Path, Utf8PathBuf};
+    ///
+    /// let path = Utf8Path::new("src/foo.rs");
+    /// assert_eq!(path.with_file_stem("bar"), Utf8PathBuf::from("src/bar.rs"));
+    ///
+    /// let path = Utf8Path::new("foo.tar.gz");
+    /// assert_eq!(path.with_file_stem("bar"), Utf8PathBuf::from("bar.gz"));
+    /// ```
+    #[must_use]
+    pub fn with_file_stem(&self, file_stem: impl AsRef<str>) -> Utf8PathBuf {
+        let mut buf = self.to_path_buf();
+        if self.file_name().is_some() {
+            let file_stem = file_stem.as_ref();
+            match self.extension() {
+                Some(extension) => buf.set_file_name(format!("{file_stem}.{extension}")),
+                None => buf.set_file_name(file_stem),
+            }
+        }
+        buf
+    }
+
+    /// Creates an owned [`Utf8PathBuf`] like `self` but with all extensions replaced by
+    /// `extensions`.
+    ///
+    /// Unlike [`with_extension`](Self::with_extension), which only replaces the portion of the
+    /// file name after the final `.`, this replaces everything after the
+    /// [file prefix](Self::strip_all_extensions). `extensions` may itself contain several
+    /// `.`-separated parts. If it is empty, all extensions are removed.
+    ///
+    /// If [`self.file_name`] is [`None`], this returns `self` unchanged.
+    ///
+    /// [`self.file_name`]: Utf8Path::file_name
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use camino::{Utf8Path, Utf8PathBuf};
+    ///
+    /// let path = Utf8Path::new("dist/foo.tar.gz");
+    /// assert_eq!(path.with_extensions("zip"), Utf8PathBuf::from("dist/foo.zip"));
+    /// assert_eq!(path.with_extensions("tar.xz"), Utf8PathBuf::from("dist/foo.tar.xz"));
+    /// assert_eq!(path.with_extensions(""), Utf8PathBuf::from("dist/foo"));
+    /// ```
+    #[must_use]
+    pub fn with_extensions(&self, extensions: impl AsRef<str>) -> Utf8PathBuf {
+        let mut buf = self.to_path_buf();
+        if let Some(name) = self.file_name() {
+            let prefix = split_file_at_dot(name).0;
+            let extensions = extensions.as_ref();
+            if extensions.is_empty() {
+                buf.set_file_name(prefix);
+            } else {
+                buf.set_file_name(format!("{prefix}.{extensions}"));
+            }
+        }
+        buf
+    }
+
     /// Produces an iterator over the [`Utf8Component`]s of the path.
     ///
     /// When parsing the path, there is a small amount of normalization:
@@ -1320,11 +2394,567 @@ impl Utf8Path {
     /// assert_eq!(it.next(), Some("foo.txt"));
     /// assert_eq!(it.next(), None)
     /// ```
-    #[inline]
-    pub fn iter(&self) -> Iter<'_> {
-        Iter {
-            inner: self.components(),
-        }
+    #[inline]
+    pub fn iter(&self) -> Iter<'_> {
+        Iter {
+            inner: self.components(),
+        }
+    }
+
+    /// Produces an iterator over the [`Utf8Component`]s of the path, along with the range of bytes
+    /// in [`as_str`](Self::as_str) that each component occupies.
+    ///
+    /// The components are the same as those returned by [`components`](Self::components). Since
+    /// repeated separators and `.` components are skipped, the offsets can't in general be
+    /// recomputed from the components alone.
+    ///
+    /// For the root, the range covers the separator that makes up the root. On Windows, some
+    /// prefixes such as `\\server\share` imply a root even if no separator follows them, in
+    /// which case the range of the root is empty and starts at the end of the prefix.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use camino::{Utf8Component, Utf8Path};
+    ///
+    /// let path = Utf8Path::new("/usr//./lib/");
+    /// let spans: Vec<_> = path.component_spans().collect();
+    /// assert_eq!(
+    ///     spans,
+    ///     [
+    ///         (0..1, Utf8Component::RootDir),
+    ///         (1..4, Utf8Component::Normal("usr")),
+    ///         (8..11, Utf8Component::Normal("lib")),
+    ///     ],
+    /// );
+    /// assert_eq!(&path.as_str()[8..11], "lib");
+    /// ```
+    #[inline]
+    pub fn component_spans(&self) -> Utf8ComponentSpans<'_> {
+        Utf8ComponentSpans {
+            path: self.as_str(),
+            components: self.components(),
+        }
+    }
+
+    /// Returns the first component of the path, along with the rest of the path.
+    ///
+    /// Returns [`None`] if the path has no components. The rest of the path is a slice of `self`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use camino::{Utf8Component, Utf8Path};
+    ///
+    /// let path = Utf8Path::new("src/bin/main.rs");
+    /// assert_eq!(
+    ///     path.split_first(),
+    ///     Some((Utf8Component::Normal("src"), Utf8Path::new("bin/main.rs"))),
+    /// );
+    ///
+    /// let path = Utf8Path::new("/etc");
+    /// assert_eq!(
+    ///     path.split_first(),
+    ///     Some((Utf8Component::RootDir, Utf8Path::new("etc"))),
+    /// );
+    ///
+    /// assert_eq!(Utf8Path::new("").split_first(), None);
+    /// ```
+    #[must_use]
+    pub fn split_first(&self) -> Option<(Utf8Component<'_>, &Utf8Path)> {
+        let mut components = self.components();
+        let first = components.next()?;
+        Some((first, components.as_path()))
+    }
+
+    /// Returns the last component of the path, along with the rest of the path.
+    ///
+    /// Returns [`None`] if the path has no components. The rest of the path is a slice of `self`.
+    ///
+    /// Unlike [`parent`] and [`file_name`], this returns a component even if the path ends in a
+    /// root, prefix or `..`.
+    ///
+    /// [`parent`]: Utf8Path::parent
+    /// [`file_name`]: Utf8Path::file_name
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use camino::{Utf8Component, Utf8Path};
+    ///
+    /// let path = Utf8Path::new("src/bin/main.rs");
+    /// assert_eq!(
+    ///     path.split_last(),
+    ///     Some((Utf8Component::Normal("main.rs"), Utf8Path::new("src/bin"))),
+    /// );
+    ///
+    /// let path = Utf8Path::new("foo/..");
+    /// assert_eq!(
+    ///     path.split_last(),
+    ///     Some((Utf8Component::ParentDir, Utf8Path::new("foo"))),
+    /// );
+    ///
+    /// let path = Utf8Path::new("/");
+    /// assert_eq!(path.split_last(), Some((Utf8Component::RootDir, Utf8Path::new(""))));
+    /// ```
+    #[must_use]
+    pub fn split_last(&self) -> Option<(Utf8Component<'_>, &Utf8Path)> {
+        let mut components = self.components();
+        let last = components.next_back()?;
+        Some((last, components.as_path()))
+    }
+
+    /// Returns the portion of the path made up of the components in `range`.
+    ///
+    /// Components are counted as they are produced by [`components`], and the result is a slice
+    /// of `self`, so this does not allocate.
+    ///
+    /// [`components`]: Utf8Path::components
+    ///
+    /// # Panics
+    ///
+    /// Panics if the start of the range is greater than its end, or if the end of the range is
+    /// greater than the number of components in the path.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use camino::Utf8Path;
+    ///
+    /// let path = Utf8Path::new("/usr/local/lib/libfoo.so");
+    ///
+    /// assert_eq!(path.slice_components(..3), Utf8Path::new("/usr/local"));
+    /// assert_eq!(path.slice_components(1..3), Utf8Path::new("usr/local"));
+    /// assert_eq!(path.slice_components(2..), Utf8Path::new("local/lib/libfoo.so"));
+    /// assert_eq!(path.slice_components(2..2), Utf8Path::new(""));
+    /// ```
+    #[must_use]
+    pub fn slice_components(&self, range: impl RangeBounds<usize>) -> &Utf8Path {
+        let mut components = self.components();
+        let len = components.clone().count();
+
+        let start = match range.start_bound() {
+            Bound::Included(&start) => start,
+            Bound::Excluded(&start) => start
+                .checked_add(1)
+                .expect("range start index overflowed usize"),
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(&end) => end
+                .checked_add(1)
+                .expect("range end index overflowed usize"),
+            Bound::Excluded(&end) => end,
+            Bound::Unbounded => len,
+        };
+        assert!(
+            start <= end,
+            "component index starts at {start} but ends at {end}",
+        );
+        assert!(
+            end <= len,
+            "range end index {end} out of range for path with {len} components",
+        );
+
+        for _ in 0..start {
+            components.next();
+        }
+        for _ in end..len {
+            components.next_back();
+        }
+        components.as_path()
+    }
+
+    /// Normalizes a path by resolving `.` and `..` components, without accessing the filesystem.
+    ///
+    /// `.` components are removed, and each `..` component is folded into the normal component
+    /// preceding it. Any root or prefix is kept as-is. A path that normalizes to nothing, such as
+    /// `.` or `foo/..`, becomes the empty path.
+    ///
+    /// Unlike [`canonicalize_utf8`](Self::canonicalize_utf8), this does not resolve symlinks, so
+    /// if `foo` is a symlink, `foo/..` may not actually point to the same directory as the
+    /// result.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`NormalizeError`] if a `..` component would climb above the root (for absolute
+    /// paths) or above the start of the path (for relative paths).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use camino::Utf8Path;
+    ///
+    /// let path = Utf8Path::new("foo/./bar/../baz");
+    /// assert_eq!(path.normalize_lexically().unwrap(), Utf8Path::new("foo/baz"));
+    ///
+    /// let path = Utf8Path::new("/usr/lib/../../etc");
+    /// assert_eq!(path.normalize_lexically().unwrap(), Utf8Path::new("/etc"));
+    ///
+    /// assert_eq!(Utf8Path::new("./foo/..").normalize_lexically().unwrap(), "");
+    ///
+    /// assert!(Utf8Path::new("foo/../..").normalize_lexically().is_err());
+    /// assert!(Utf8Path::new("/..").normalize_lexically().is_err());
+    /// ```
+    pub fn normalize_lexically(&self) -> Result<Utf8PathBuf, NormalizeError> {
+        let mut lexical = Utf8PathBuf::new();
+        let mut iter = self.components().peekable();
+
+        // Find the root, if any, and add it to the lexical path. On Windows, "C:\" is treated as a
+        // single root even though components() splits it into a prefix and a root directory.
+        let root = match iter.peek() {
+            Some(Utf8Component::Prefix(prefix)) => {
+                lexical.push(prefix.as_str());
+                iter.next();
+                if let Some(Utf8Component::RootDir) = iter.peek() {
+                    lexical.push(Utf8Component::RootDir);
+                    iter.next();
+                }
+                lexical.as_str().len()
+            }
+            Some(Utf8Component::RootDir) => {
+                lexical.push(Utf8Component::RootDir);
+                iter.next();
+                lexical.as_str().len()
+            }
+            _ => 0,
+        };
+
+        for component in iter {
+            match component {
+                // These only appear at the start of the path, and were handled above.
+                Utf8Component::Prefix(_) | Utf8Component::RootDir => {
+                    unreachable!("prefix and root can only be at the start of a path")
+                }
+                Utf8Component::CurDir => {}
+                Utf8Component::ParentDir => {
+                    if lexical.as_str().len() == root {
+                        return Err(NormalizeError(()));
+                    }
+                    lexical.pop();
+                }
+                Utf8Component::Normal(name) => lexical.push(name),
+            }
+        }
+
+        Ok(lexical)
+    }
+
+    /// Compares `self` and `other` for equality, ignoring case.
+    ///
+    /// Paths are compared component by component, like the [`PartialEq`] implementation for
+    /// [`Utf8Path`], with each component compared after applying Unicode simple case folding. This
+    /// is the same kind of comparison that case-insensitive filesystems like the ones typically
+    /// used on Windows and macOS perform (though details vary by filesystem).
+    ///
+    /// For a type that can be used as a case-insensitive key in maps and sets, see
+    /// [`Utf8PathCaseInsensitive`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use camino::Utf8Path;
+    ///
+    /// let path = Utf8Path::new("/Users/Ferris/Documents/README.md");
+    /// assert!(path.eq_ignore_case("/users/ferris/documents/readme.md"));
+    /// assert!(path.eq_ignore_case("/USERS//FERRIS/DOCUMENTS/README.MD"));
+    /// assert!(!path.eq_ignore_case("/users/ferris/documents"));
+    ///
+    /// // Non-ASCII characters are case folded as well.
+    /// assert!(Utf8Path::new("ΣΊΣΥΦΟΣ").eq_ignore_case("σίσυφος"));
+    /// ```
+    #[must_use]
+    pub fn eq_ignore_case(&self, other: impl AsRef<Utf8Path>) -> bool {
+        self.cmp_ignore_case(other) == Ordering::Equal
+    }
+
+    /// Compares `self` and `other` for ordering, ignoring case.
+    ///
+    /// This is consistent with [`eq_ignore_case`](Self::eq_ignore_case): paths are compared
+    /// component by component, with each component compared after applying Unicode simple case
+    /// folding.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use camino::Utf8Path;
+    /// use std::cmp::Ordering;
+    ///
+    /// let mut paths = vec![Utf8Path::new("b.txt"), Utf8Path::new("C.txt"), Utf8Path::new("A.txt")];
+    /// paths.sort_by(|a, b| a.cmp_ignore_case(b));
+    /// assert_eq!(paths, ["A.txt", "b.txt", "C.txt"]);
+    ///
+    /// assert_eq!(Utf8Path::new("foo/BAR").cmp_ignore_case("FOO/bar"), Ordering::Equal);
+    /// ```
+    #[must_use]
+    pub fn cmp_ignore_case(&self, other: impl AsRef<Utf8Path>) -> Ordering {
+        self.components()
+            .map(CaseFoldedComponent)
+            .cmp(other.as_ref().components().map(CaseFoldedComponent))
+    }
+
+    /// Compares `self` and `other` in natural order, comparing runs of digits numerically.
+    ///
+    /// Paths are compared component by component, like the [`Ord`] implementation for
+    /// [`Utf8Path`]. Within each component, runs of ASCII digits are compared by their numeric
+    /// value, and everything else is compared character by character. This means that `file2`
+    /// sorts before `file10`, and `v1.9` before `v1.10`.
+    ///
+    /// Components that only differ in leading zeros, such as `01` and `1`, are ordered as plain
+    /// strings. This keeps the ordering consistent with equality: `cmp_natural` only returns
+    /// [`Ordering::Equal`] for paths that are equal.
+    ///
+    /// For a type that can be used as a key in ordered maps and sets, see [`Utf8PathNaturalOrd`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use camino::Utf8Path;
+    ///
+    /// let mut paths = vec![
+    ///     Utf8Path::new("v1.10/file10.txt"),
+    ///     Utf8Path::new("v1.9/file2.txt"),
+    ///     Utf8Path::new("v1.10/file2.txt"),
+    ///     Utf8Path::new("v1.9/file10.txt"),
+    /// ];
+    /// paths.sort_by(|a, b| a.cmp_natural(b));
+    /// assert_eq!(
+    ///     paths,
+    ///     ["v1.9/file2.txt", "v1.9/file10.txt", "v1.10/file2.txt", "v1.10/file10.txt"],
+    /// );
+    /// ```
+    #[must_use]
+    pub fn cmp_natural(&self, other: impl AsRef<Utf8Path>) -> Ordering {
+        self.components()
+            .map(NaturalComponent)
+            .cmp(other.as_ref().components().map(NaturalComponent))
+    }
+
+    /// Compares `self` and `other` in tree order.
+    ///
+    /// In tree order, a directory sorts immediately before its contents, and its contents sort
+    /// before any of its siblings. Paths are compared component by component, so this is
+    /// identical to [`Ord::cmp`] on [`Utf8Path`], which already sorts paths in tree order. This
+    /// method is provided to make that intent explicit, and it matches the byte order of
+    /// [`sort_key`](Self::sort_key). Comparing the paths as strings doesn't have this property,
+    /// because separators sort after characters such as `-` and `.`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use camino::Utf8Path;
+    ///
+    /// let mut paths = vec![Utf8Path::new("a.txt"), Utf8Path::new("a/b"), Utf8Path::new("a")];
+    /// paths.sort_by(|a, b| a.cmp_tree_order(b));
+    /// assert_eq!(paths, ["a", "a/b", "a.txt"]);
+    ///
+    /// // Compare with sorting by string, which puts the sibling a.txt in between a and a/b.
+    /// paths.sort_by_key(|path| path.as_str());
+    /// assert_eq!(paths, ["a", "a.txt", "a/b"]);
+    ///
+    /// // Sorting with Ord gives the same result.
+    /// paths.sort();
+    /// assert_eq!(paths, ["a", "a/b", "a.txt"]);
+    /// ```
+    #[must_use]
+    pub fn cmp_tree_order(&self, other: impl AsRef<Utf8Path>) -> Ordering {
+        self.components().cmp(other.as_ref().components())
+    }
+
+    /// Returns a byte string that sorts the same way as the path.
+    ///
+    /// For any two paths `a` and `b`, comparing `a.sort_key()` with `b.sort_key()` byte by byte
+    /// gives the same result as [`a.cmp_tree_order(b)`](Self::cmp_tree_order), and keys are
+    /// equal exactly when the paths are equal. In effect, separators sort below every character.
+    /// This makes sort keys suitable for storing paths in databases and key-value stores that
+    /// order keys by their bytes, where range scans can then be used to find everything under a
+    /// directory.
+    ///
+    /// The key is not valid UTF-8, and is only meant to be compared with other sort keys. Its
+    /// format is as follows: each component is encoded as a tag byte identifying its kind
+    /// (`1` for a prefix, `2` for a root, `3` for `.`, `4` for `..` and `5` for a normal
+    /// component), followed by its contents for prefixes and normal components. Contents are
+    /// terminated by the bytes `0x00 0x01`, with any NUL bytes within them escaped as `0x00 0xFF`.
+    /// (Prefixes are encoded as a byte identifying the kind of prefix, followed by each of its
+    /// parts.)
+    ///
+    /// To append the key to an existing buffer, use [`write_sort_key`](Self::write_sort_key).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use camino::Utf8Path;
+    ///
+    /// let a = Utf8Path::new("a/b");
+    /// let b = Utf8Path::new("a-b");
+    /// assert!(a.as_str() > b.as_str());
+    /// assert!(a < b);
+    /// assert!(a.sort_key() < b.sort_key());
+    ///
+    /// // Equal paths have equal keys.
+    /// assert_eq!(Utf8Path::new("a//b/").sort_key(), a.sort_key());
+    /// ```
+    #[must_use]
+    pub fn sort_key(&self) -> Vec<u8> {
+        // Each component needs at least one more byte than it takes up in the path, and usually
+        // two or three.
+        let mut key = Vec::with_capacity(self.as_str().len() + 16);
+        self.write_sort_key(&mut key);
+        key
+    }
+
+    /// Appends the [sort key](Self::sort_key) for the path to `buf`.
+    ///
+    /// This can be used to avoid allocating a new buffer for each key, or to build up a
+    /// composite key of which the path is a part.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use camino::Utf8Path;
+    ///
+    /// let mut buf = Vec::new();
+    /// for path in ["src/lib.rs", "src/main.rs"] {
+    ///     buf.clear();
+    ///     Utf8Path::new(path).write_sort_key(&mut buf);
+    ///     assert_eq!(buf, Utf8Path::new(path).sort_key());
+    /// }
+    /// ```
+    pub fn write_sort_key(&self, buf: &mut Vec<u8>) {
+        fn write_part(buf: &mut Vec<u8>, part: &[u8]) {
+            for &b in part {
+                if b == 0 {
+                    buf.extend_from_slice(&[0x00, 0xFF]);
+                } else {
+                    buf.push(b);
+                }
+            }
+            buf.extend_from_slice(&[0x00, 0x01]);
+        }
+
+        for component in self.components() {
+            buf.push(component.kind_rank() + 1);
+            match component {
+                Utf8Component::Prefix(prefix) => match prefix.kind() {
+                    // The prefix kinds are ordered the same way as in Utf8Prefix's Ord impl.
+                    Utf8Prefix::Verbatim(prefix) => {
+                        buf.push(0);
+                        write_part(buf, prefix.as_bytes());
+                    }
+                    Utf8Prefix::VerbatimUNC(server, share) => {
+                        buf.push(1);
+                        write_part(buf, server.as_bytes());
+                        write_part(buf, share.as_bytes());
+                    }
+                    Utf8Prefix::VerbatimDisk(drive) => buf.extend_from_slice(&[2, drive]),
+                    Utf8Prefix::DeviceNS(device) => {
+                        buf.push(3);
+                        write_part(buf, device.as_bytes());
+                    }
+                    Utf8Prefix::UNC(server, share) => {
+                        buf.push(4);
+                        write_part(buf, server.as_bytes());
+                        write_part(buf, share.as_bytes());
+                    }
+                    Utf8Prefix::Disk(drive) => buf.extend_from_slice(&[5, drive]),
+                },
+                Utf8Component::RootDir | Utf8Component::CurDir | Utf8Component::ParentDir => {}
+                Utf8Component::Normal(name) => write_part(buf, name.as_bytes()),
+            }
+        }
+    }
+
+    /// Returns a hash of the path that is the same on every platform and Rust version.
+    ///
+    /// The [`Hash`] implementation for [`Utf8Path`] is only meant for use within a single
+    /// process, and its output may differ between platforms, Rust versions and even runs. In
+    /// contrast, `stable_hash` is suitable for keys that are persisted or shared between
+    /// machines, such as in a distributed build cache.
+    ///
+    /// The path is hashed in normalized form: its [`components`](Self::components) are joined
+    /// together with `/`, so repeated separators, `.` components (other than at the beginning)
+    /// and trailing separators don't affect the result. On Windows, where `\` is also a
+    /// separator, this means that `a\b` and `a/b` hash to the same value, which is the same as the
+    /// hash of `a/b` on Unix. Windows prefixes are normalized too, so `c:\foo` and `C:\foo` hash
+    /// to the same value. The normalized form is then hashed with 64-bit
+    /// [FNV-1a](http://www.isthe.com/chongo/tech/comp/fnv/index.html).
+    ///
+    /// This is not a cryptographic hash. To feed the normalized form into a hasher of your choice
+    /// instead, use [`hash_stable`](Self::hash_stable).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use camino::Utf8Path;
+    ///
+    /// let path = Utf8Path::new("src/lib.rs");
+    /// assert_eq!(path.stable_hash(), 0x2fba_4152_af2c_3a3a);
+    /// assert_eq!(Utf8Path::new("src//./lib.rs/").stable_hash(), path.stable_hash());
+    /// # #[cfg(windows)]
+    /// assert_eq!(Utf8Path::new(r"src\lib.rs").stable_hash(), path.stable_hash());
+    /// ```
+    #[must_use]
+    pub fn stable_hash(&self) -> u64 {
+        const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+        const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+        let mut hash = FNV_OFFSET_BASIS;
+        self.for_each_stable_part(|part| {
+            for &b in part {
+                hash ^= u64::from(b);
+                hash = hash.wrapping_mul(FNV_PRIME);
+            }
+        });
+        hash
+    }
+
+    /// Feeds the normalized form of the path into the given [`Hasher`].
+    ///
+    /// The normalized form is the same one that [`stable_hash`](Self::stable_hash) uses: the
+    /// path's components joined together with `/`. It is written to `state` using only
+    /// [`Hasher::write`] and [`Hasher::write_u8`], so given a hasher whose output is stable
+    /// across platforms (which [`DefaultHasher`](std::collections::hash_map::DefaultHasher) is
+    /// not), the result is stable as well.
+    ///
+    /// The normalized form is followed by a `0xFF` byte, which can't occur in UTF-8 text, so that
+    /// paths can be hashed as part of a larger key without ambiguity.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use camino::Utf8Path;
+    /// use std::{collections::hash_map::DefaultHasher, hash::Hasher};
+    ///
+    /// // A hasher that records the bytes written to it.
+    /// #[derive(Default)]
+    /// struct Recorder(Vec<u8>);
+    ///
+    /// impl Hasher for Recorder {
+    ///     fn write(&mut self, bytes: &[u8]) {
+    ///         self.0.extend_from_slice(bytes);
+    ///     }
+    ///
+    ///     // Not a good hash function, but enough for this example.
+    ///     fn finish(&self) -> u64 {
+    ///         self.0.iter().map(|&b| u64::from(b)).sum()
+    ///     }
+    /// }
+    ///
+    /// let mut recorder = Recorder::default();
+    /// Utf8Path::new("/usr//lib/./").hash_stable(&mut recorder);
+    /// assert_eq!(recorder.0, b"/usr/lib\xFF");
+    ///
+    /// // Any other hasher works too, though DefaultHasher's output isn't stable across Rust
+    /// // versions.
+    /// let mut a = DefaultHasher::new();
+    /// Utf8Path::new("a/b").hash_stable(&mut a);
+    /// let mut b = DefaultHasher::new();
+    /// Utf8Path::new("a//b/").hash_stable(&mut b);
+    /// assert_eq!(a.finish(), b.finish());
+    /// ```
+    pub fn hash_stable<H: Hasher>(&self, state: &mut H) {
+        self.for_each_stable_part(|part| state.write(part));
+        state.write_u8(0xFF);
     }
 
     /// Queries the file system to get information about a file, directory, etc.
@@ -1776,6 +3406,59 @@ impl Utf8Path {
     unsafe fn assume_utf8_mut(path: &mut Path) -> &mut Utf8Path {
         &mut *(path as *mut Path as *mut Utf8Path)
     }
+
+    // Returns the byte range of each component of the path within the string, in the order that
+    // they're produced by components().
+    fn component_ranges(&self) -> Vec<Range<usize>> {
+        self.component_spans().map(|(range, _)| range).collect()
+    }
+
+    // Calls f with each part of the normalized form of the path used by stable_hash, in order.
+    fn for_each_stable_part(&self, mut f: impl FnMut(&[u8])) {
+        // Whether a separator is needed before the next normal component.
+        let mut needs_separator = false;
+        for component in self.components() {
+            match component {
+                // Prefixes that compare equal, such as `c:` and `C:`, must hash the same, so they
+                // are hashed in a canonical form rather than as written.
+                Utf8Component::Prefix(prefix) => match prefix.kind() {
+                    Utf8Prefix::Verbatim(name) => {
+                        f(b"//?/");
+                        f(name.as_bytes());
+                    }
+                    Utf8Prefix::VerbatimUNC(server, share) => {
+                        f(b"//?/UNC/");
+                        f(server.as_bytes());
+                        f(b"/");
+                        f(share.as_bytes());
+                    }
+                    Utf8Prefix::VerbatimDisk(disk) => {
+                        f(b"//?/");
+                        f(&[disk.to_ascii_uppercase(), b':']);
+                    }
+                    Utf8Prefix::DeviceNS(name) => {
+                        f(b"//./");
+                        f(name.as_bytes());
+                    }
+                    Utf8Prefix::UNC(server, share) => {
+                        f(b"//");
+                        f(server.as_bytes());
+                        f(b"/");
+                        f(share.as_bytes());
+                    }
+                    Utf8Prefix::Disk(disk) => f(&[disk.to_ascii_uppercase(), b':']),
+                },
+                Utf8Component::RootDir => f(b"/"),
+                Utf8Component::CurDir | Utf8Component::ParentDir | Utf8Component::Normal(_) => {
+                    if needs_separator {
+                        f(b"/");
+                    }
+                    f(component.as_str().as_bytes());
+                    needs_separator = true;
+                }
+            }
+        }
+    }
 }
 
 impl Clone for Box<Utf8Path> {
@@ -1803,6 +3486,32 @@ impl fmt::Debug for Utf8Path {
     }
 }
 
+/// Joins a path onto this one, returning a new [`Utf8PathBuf`]. Equivalent to
+/// [`Utf8Path::join`].
+///
+/// Only the first `/` allocates: the resulting [`Utf8PathBuf`] is reused by any further `/`
+/// operations.
+///
+/// # Examples
+///
+/// ```
+/// use camino::Utf8Path;
+///
+/// let root = Utf8Path::new("/project");
+/// assert_eq!(root / "src" / "main.rs", Utf8Path::new("/project/src/main.rs"));
+///
+/// // As with join, an absolute path replaces the current one.
+/// assert_eq!(root / "/etc", Utf8Path::new("/etc"));
+/// ```
+impl<P: AsRef<Utf8Path>> std::ops::Div<P> for &Utf8Path {
+    type Output = Utf8PathBuf;
+
+    #[inline]
+    fn div(self, path: P) -> Utf8PathBuf {
+        self.join(path)
+    }
+}
+
 /// An iterator over [`Utf8Path`] and its ancestors.
 ///
 /// This `struct` is created by the [`ancestors`] method on [`Utf8Path`].
@@ -2047,6 +3756,91 @@ impl<'a> DoubleEndedIterator for Iter<'a> {
 
 impl FusedIterator for Iter<'_> {}
 
+/// An iterator over the extensions of a [`Utf8Path`]'s file name, as [`str`] slices.
+///
+/// This `struct` is created by the [`extensions`] method on [`Utf8Path`].
+/// See its documentation for more.
+///
+/// [`extensions`]: Utf8Path::extensions
+#[derive(Clone, Debug)]
+#[must_use = "iterators are lazy and do nothing unless consumed"]
+pub struct Extensions<'a> {
+    inner: Option<std::str::Split<'a, char>>,
+}
+
+impl<'a> Iterator for Extensions<'a> {
+    type Item = &'a str;
+
+    #[inline]
+    fn next(&mut self) -> Option<&'a str> {
+        self.inner.as_mut().and_then(|inner| inner.next())
+    }
+}
+
+impl<'a> DoubleEndedIterator for Extensions<'a> {
+    #[inline]
+    fn next_back(&mut self) -> Option<&'a str> {
+        self.inner.as_mut().and_then(|inner| inner.next_back())
+    }
+}
+
+impl FusedIterator for Extensions<'_> {}
+
+/// An iterator over the [`Utf8Component`]s of a [`Utf8Path`], along with their byte ranges in the
+/// path.
+///
+/// This `struct` is created by the [`component_spans`] method on [`Utf8Path`].
+/// See its documentation for more.
+///
+/// [`component_spans`]: Utf8Path::component_spans
+#[derive(Clone, Debug)]
+#[must_use = "iterators are lazy and do nothing unless consumed"]
+pub struct Utf8ComponentSpans<'a> {
+    path: &'a str,
+    components: Utf8Components<'a>,
+}
+
+impl<'a> Utf8ComponentSpans<'a> {
+    // Returns the offset of s, which must be a slice of self.path.
+    fn offset_of(&self, s: &str) -> usize {
+        s.as_ptr() as usize - self.path.as_ptr() as usize
+    }
+}
+
+// Utf8Component::as_str doesn't always return a slice of the path (e.g. for the root, it returns a
+// static string), but Utf8Components::as_path always does. The remaining path begins at the start
+// of the next component and ends at the end of the next component from the back.
+impl<'a> Iterator for Utf8ComponentSpans<'a> {
+    type Item = (Range<usize>, Utf8Component<'a>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let start = self.offset_of(self.components.as_path().as_str());
+        let component = self.components.next()?;
+        // Some Windows prefixes, such as \\server\share, imply a root without a separator
+        // after them, in which case the span is empty.
+        let len = match component {
+            Utf8Component::RootDir if !self.path[start..].starts_with(is_separator) => 0,
+            _ => component.as_str().len(),
+        };
+        Some((start..start + len, component))
+    }
+}
+
+impl<'a> DoubleEndedIterator for Utf8ComponentSpans<'a> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let remaining = self.components.as_path().as_str();
+        let end = self.offset_of(remaining) + remaining.len();
+        let component = self.components.next_back()?;
+        let len = match component {
+            Utf8Component::RootDir if !self.path[..end].ends_with(is_separator) => 0,
+            _ => component.as_str().len(),
+        };
+        Some((end - len..end, component))
+    }
+}
+
+impl FusedIterator for Utf8ComponentSpans<'_> {}
+
 /// A single component of a path.
 ///
 /// A [`Utf8Component`] roughly corresponds to a substring between path separators
@@ -2108,6 +3902,18 @@ impl<'a> Utf8Component<'a> {
         }
     }
 
+    // Returns a number that orders component kinds the same way as the derived Ord impl, for use
+    // in comparisons that look at the component's string rather than the component itself.
+    pub(crate) fn kind_rank(&self) -> u8 {
+        match self {
+            Utf8Component::Prefix(_) => 0,
+            Utf8Component::RootDir => 1,
+            Utf8Component::CurDir => 2,
+            Utf8Component::ParentDir => 3,
+            Utf8Component::Normal(_) => 4,
+        }
+    }
+
     /// Extracts the underlying [`str`] slice.
     ///
     /// # Examples
@@ -2373,6 +4179,92 @@ impl fmt::Display for Utf8PrefixComponent<'_> {
     }
 }
 
+/// The kind of a [`Utf8Path`], as returned by [`Utf8Path::kind`].
+///
+/// This combines the information from [`Utf8Path::has_root`] and the path's [`Utf8Prefix`] (if
+/// any) into a single value. On Unix, paths are always either [`Relative`](Self::Relative) or
+/// [`Absolute`](Self::Absolute); the other variants only occur on Windows.
+///
+/// # Examples
+///
+/// ```
+/// use camino::{Utf8Path, Utf8PathKind};
+///
+/// assert_eq!(Utf8Path::new("foo/bar").kind(), Utf8PathKind::Relative);
+///
+/// # if cfg!(unix) {
+/// assert_eq!(Utf8Path::new("/foo/bar").kind(), Utf8PathKind::Absolute);
+/// # }
+///
+/// # if cfg!(windows) {
+/// assert_eq!(Utf8Path::new(r"C:\foo").kind(), Utf8PathKind::Absolute);
+/// assert_eq!(Utf8Path::new(r"\foo").kind(), Utf8PathKind::RootRelative);
+/// assert_eq!(Utf8Path::new(r"C:foo").kind(), Utf8PathKind::DriveRelative);
+/// assert_eq!(Utf8Path::new(r"\\server\share\foo").kind(), Utf8PathKind::Unc);
+/// assert_eq!(Utf8Path::new(r"\\?\C:\foo").kind(), Utf8PathKind::Verbatim);
+/// assert_eq!(Utf8Path::new(r"\\.\COM1").kind(), Utf8PathKind::Device);
+/// # }
+/// ```
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum Utf8PathKind {
+    /// A path without a prefix or root, e.g. `foo/bar`, which is relative to the current
+    /// directory.
+    Relative,
+
+    /// An absolute path, e.g. `/foo/bar` on Unix or `C:\foo\bar` on Windows.
+    Absolute,
+
+    /// A path that starts with a separator but has no prefix, e.g. `\foo\bar` on Windows, which
+    /// is relative to the root of the current drive.
+    ///
+    /// On Unix, such paths are [`Absolute`](Self::Absolute).
+    RootRelative,
+
+    /// A path with a drive prefix but no root, e.g. `C:foo\bar` on Windows, which is relative to
+    /// the current directory on that drive.
+    DriveRelative,
+
+    /// A path on a network share, e.g. `\\server\share\foo` on Windows.
+    Unc,
+
+    /// A verbatim path, e.g. `\\?\C:\foo` or `\\?\UNC\server\share\foo` on Windows, in which `/`
+    /// is not treated as a separator.
+    Verbatim,
+
+    /// A path to a device in the Win32 device namespace, e.g. `\\.\COM1` on Windows.
+    Device,
+}
+
+impl Utf8PathKind {
+    /// Returns `true` if paths of this kind are absolute, i.e. don't depend on the current
+    /// directory or drive.
+    ///
+    /// This is consistent with [`Utf8Path::is_absolute`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use camino::Utf8PathKind;
+    ///
+    /// assert!(Utf8PathKind::Absolute.is_absolute());
+    /// assert!(Utf8PathKind::Unc.is_absolute());
+    /// assert!(!Utf8PathKind::DriveRelative.is_absolute());
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn is_absolute(self) -> bool {
+        match self {
+            Utf8PathKind::Absolute
+            | Utf8PathKind::Unc
+            | Utf8PathKind::Verbatim
+            | Utf8PathKind::Device => true,
+            Utf8PathKind::Relative | Utf8PathKind::RootRelative | Utf8PathKind::DriveRelative => {
+                false
+            }
+        }
+    }
+}
+
 // ---
 // read_dir_utf8
 // ---
@@ -3262,6 +5154,91 @@ impl error::Error for FromBoxedPathError {
     }
 }
 
+/// An error returned by [`Utf8Path::normalize_lexically`] if a `..` component would climb above
+/// the root or the start of the path.
+///
+/// # Examples
+///
+/// ```
+/// use camino::{Utf8Path, NormalizeError};
+///
+/// let err: NormalizeError = Utf8Path::new("foo/../../bar")
+///     .normalize_lexically()
+///     .expect_err("climbs above the start of the path");
+/// ```
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct NormalizeError(());
+
+impl fmt::Display for NormalizeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "parent reference `..` points outside of base directory")
+    }
+}
+
+impl error::Error for NormalizeError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        None
+    }
+}
+
+/// An error returned by [`Utf8Path::join_within`] if the joined path would not stay within the
+/// base directory.
+///
+/// # Examples
+///
+/// ```
+/// use camino::{Utf8Path, JoinWithinError};
+///
+/// let err: JoinWithinError = Utf8Path::new("/srv/www")
+///     .join_within("../secrets.txt")
+///     .expect_err("escapes the base directory");
+/// assert_eq!(err.as_path(), "../secrets.txt");
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct JoinWithinError {
+    path: Utf8PathBuf,
+    kind: JoinWithinErrorKind,
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+enum JoinWithinErrorKind {
+    HasRoot,
+    HasPrefix,
+    EscapesRoot,
+}
+
+impl JoinWithinError {
+    /// Returns the [`Utf8Path`] that was attempted to be joined.
+    #[inline]
+    pub fn as_path(&self) -> &Utf8Path {
+        &self.path
+    }
+
+    /// Returns the [`Utf8PathBuf`] that was attempted to be joined.
+    #[inline]
+    pub fn into_path_buf(self) -> Utf8PathBuf {
+        self.path
+    }
+}
+
+impl fmt::Display for JoinWithinError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.kind {
+            JoinWithinErrorKind::HasRoot => write!(f, "path has a root: {}", self.path),
+            JoinWithinErrorKind::HasPrefix => write!(f, "path has a prefix: {}", self.path),
+            JoinWithinErrorKind::EscapesRoot => {
+                write!(f, "path escapes the base directory: {}", self.path)
+            }
+        }
+    }
+}
+
+impl error::Error for JoinWithinError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        None
+    }
+}
+
 // ---
 // AsRef impls
 // ---
@@ -3633,6 +5610,204 @@ impl_cmp_os_str!(&'a Utf8Path, Cow<'b, OsStr>);
 impl_cmp_os_str!(&'a Utf8Path, OsString);
 // NOTE: impls for Cow<'a, Utf8Path> cannot be defined because of the orphan rule (E0117)
 
+// ---
+// Case-insensitive comparisons
+// ---
+
+path_wrapper! {
+    /// A wrapper around a path that compares, orders and hashes it ignoring case.
+    ///
+    /// The [`PartialEq`], [`Ord`] and [`Hash`] implementations on [`Utf8Path`] and
+    /// [`Utf8PathBuf`] are always case-sensitive. This wrapper instead uses
+    /// [`Utf8Path::eq_ignore_case`] and [`Utf8Path::cmp_ignore_case`], and can be used as a key in
+    /// maps and sets to model case-insensitive filesystems.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use camino::{Utf8Path, Utf8PathBuf, Utf8PathCaseInsensitive};
+    /// use std::collections::HashSet;
+    ///
+    /// let mut files = HashSet::new();
+    /// assert!(files.insert(Utf8PathCaseInsensitive::new(Utf8PathBuf::from("src/Lib.rs"))));
+    /// assert!(!files.insert(Utf8PathCaseInsensitive::new(Utf8PathBuf::from("SRC/lib.RS"))));
+    ///
+    /// let key = Utf8PathCaseInsensitive::new(Utf8PathBuf::from("src/lib.rs"));
+    /// assert_eq!(files.get(&key).unwrap().as_path(), "src/Lib.rs");
+    /// ```
+    pub struct Utf8PathCaseInsensitive;
+
+    /// Wraps a path so that it is compared, ordered and hashed ignoring case.
+    fn new;
+
+    fn eq(a, b) { a.eq_ignore_case(b) }
+    fn cmp(a, b) { a.cmp_ignore_case(b) }
+    fn hash(path, state) {
+        for component in path.components() {
+            CaseFoldedComponent(component).hash(state);
+        }
+    }
+}
+
+mapped_component! {
+    // A path component that is compared, ordered and hashed after case folding.
+    struct CaseFoldedComponent;
+
+    fn chars(s) { s.chars().map(simple_case_fold) }
+}
+
+// Applies Unicode simple case folding (the C + S mappings in CaseFolding.txt) to a character.
+//
+// For almost all characters, this is the same as mapping the character to lowercase. The
+// exceptions are characters that either lowercase to more than one character (which simple case
+// folding leaves alone), or that are already lowercase but fold to a different character. (Cherokee
+// letters fold to uppercase rather than lowercase, but since only equality and consistent ordering
+// matter here, lowercasing them is equivalent.)
+fn simple_case_fold(c: char) -> char {
+    match c {
+        '\u{00B5}' => '\u{03BC}', // MICRO SIGN -> GREEK SMALL LETTER MU
+        '\u{017F}' => 's',        // LATIN SMALL LETTER LONG S
+        '\u{0345}' => '\u{03B9}', // COMBINING GREEK YPOGEGRAMMENI -> GREEK SMALL LETTER IOTA
+        '\u{03C2}' => '\u{03C3}', // GREEK SMALL LETTER FINAL SIGMA -> GREEK SMALL LETTER SIGMA
+        '\u{03D0}' => '\u{03B2}', // GREEK BETA SYMBOL
+        '\u{03D1}' => '\u{03B8}', // GREEK THETA SYMBOL
+        '\u{03D5}' => '\u{03C6}', // GREEK PHI SYMBOL
+        '\u{03D6}' => '\u{03C0}', // GREEK PI SYMBOL
+        '\u{03F0}' => '\u{03BA}', // GREEK KAPPA SYMBOL
+        '\u{03F1}' => '\u{03C1}', // GREEK RHO SYMBOL
+        '\u{03F5}' => '\u{03B5}', // GREEK LUNATE EPSILON SYMBOL
+        '\u{1C80}' => '\u{0432}', // CYRILLIC SMALL LETTER ROUNDED VE
+        '\u{1C81}' => '\u{0434}', // CYRILLIC SMALL LETTER LONG-LEGGED DE
+        '\u{1C82}' => '\u{043E}', // CYRILLIC SMALL LETTER NARROW O
+        '\u{1C83}' => '\u{0441}', // CYRILLIC SMALL LETTER WIDE ES
+        '\u{1C84}' | '\u{1C85}' => '\u{0442}', // CYRILLIC SMALL LETTER TALL TE, THREE-LEGGED TE
+        '\u{1C86}' => '\u{044A}', // CYRILLIC SMALL LETTER TALL HARD SIGN
+        '\u{1C87}' => '\u{0463}', // CYRILLIC SMALL LETTER TALL YAT
+        '\u{1C88}' => '\u{A64B}', // CYRILLIC SMALL LETTER UNBLENDED UK
+        '\u{1E9B}' => '\u{1E61}', // LATIN SMALL LETTER LONG S WITH DOT ABOVE
+        '\u{1FBE}' => '\u{03B9}', // GREEK PROSGEGRAMMENI -> GREEK SMALL LETTER IOTA
+        '\u{1FD3}' => '\u{0390}', // GREEK SMALL LETTER IOTA WITH DIALYTIKA AND OXIA -> TONOS
+        '\u{1FE3}' => '\u{03B0}', // GREEK SMALL LETTER UPSILON WITH DIALYTIKA AND OXIA -> TONOS
+        '\u{FB05}' => '\u{FB06}', // LATIN SMALL LIGATURE LONG S T -> LATIN SMALL LIGATURE ST
+        _ => {
+            let mut lower = c.to_lowercase();
+            match (lower.next(), lower.next()) {
+                (Some(lower), None) => lower,
+                // Only U+0130 LATIN CAPITAL LETTER I WITH DOT ABOVE lowercases to multiple
+                // characters, and it has no simple case folding.
+                _ => c,
+            }
+        }
+    }
+}
+
+// ---
+// Natural ordering
+// ---
+
+path_wrapper! {
+    /// A wrapper around a path that orders it naturally, comparing runs of digits numerically.
+    ///
+    /// The [`Ord`] implementations on [`Utf8Path`] and [`Utf8PathBuf`] compare components as
+    /// plain strings, so `file10` sorts before `file2`. This wrapper instead uses
+    /// [`Utf8Path::cmp_natural`], which is usually the order that people expect to see paths
+    /// listed in.
+    ///
+    /// Equality and hashing are the same as for [`Utf8Path`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use camino::{Utf8Path, Utf8PathNaturalOrd};
+    /// use std::collections::BTreeSet;
+    ///
+    /// let files: BTreeSet<_> = ["img10.png", "img2.png", "img1.png"]
+    ///     .into_iter()
+    ///     .map(|path| Utf8PathNaturalOrd::new(Utf8Path::new(path)))
+    ///     .collect();
+    ///
+    /// let files: Vec<_> = files.into_iter().map(Utf8PathNaturalOrd::into_inner).collect();
+    /// assert_eq!(files, ["img1.png", "img2.png", "img10.png"]);
+    /// ```
+    pub struct Utf8PathNaturalOrd;
+
+    /// Wraps a path so that it is ordered naturally.
+    fn new;
+
+    fn eq(a, b) { a == b }
+    fn cmp(a, b) { a.cmp_natural(b) }
+    fn hash(path, state) { path.hash(state) }
+}
+
+// A path component that is ordered naturally.
+struct NaturalComponent<'a>(Utf8Component<'a>);
+
+impl PartialEq for NaturalComponent<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl Eq for NaturalComponent<'_> {}
+
+impl PartialOrd for NaturalComponent<'_> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for NaturalComponent<'_> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0
+            .kind_rank()
+            .cmp(&other.0.kind_rank())
+            .then_with(|| cmp_natural_str(self.0.as_str(), other.0.as_str()))
+    }
+}
+
+// Compares two strings, treating each run of ASCII digits as a single number.
+//
+// Numbers that only differ in leading zeros (e.g. "01" and "1") compare equal at first. To keep the
+// ordering consistent with string equality, such strings are then compared as plain strings.
+fn cmp_natural_str(a: &str, b: &str) -> Ordering {
+    fn split_digits(s: &str) -> (&str, &str) {
+        let end = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+        s.split_at(end)
+    }
+
+    let (mut a_rest, mut b_rest) = (a, b);
+    loop {
+        let (x, y) = match (a_rest.chars().next(), b_rest.chars().next()) {
+            (Some(x), Some(y)) => (x, y),
+            (None, None) => return a.cmp(b),
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+        };
+
+        if x.is_ascii_digit() && y.is_ascii_digit() {
+            let (a_digits, a_tail) = split_digits(a_rest);
+            let (b_digits, b_tail) = split_digits(b_rest);
+            let a_digits = a_digits.trim_start_matches('0');
+            let b_digits = b_digits.trim_start_matches('0');
+            // Without leading zeros, a longer number is always greater.
+            let ordering = a_digits
+                .len()
+                .cmp(&b_digits.len())
+                .then_with(|| a_digits.cmp(b_digits));
+            if ordering != Ordering::Equal {
+                return ordering;
+            }
+            a_rest = a_tail;
+            b_rest = b_tail;
+        } else if x != y {
+            return x.cmp(&y);
+        } else {
+            a_rest = &a_rest[x.len_utf8()..];
+            b_rest = &b_rest[y.len_utf8()..];
+        }
+    }
+}
+
 /// Makes the path absolute without accessing the filesystem, converting it to a [`Utf8PathBuf`].
 ///
 /// If the path is relative, the current directory is used as the base directory. All intermediate
@@ -3719,6 +5894,160 @@ pub fn absolute_utf8<P: AsRef<Path>>(path: P) -> io::Result<Utf8PathBuf> {
     Utf8PathBuf::try_from(std::path::absolute(path)?).map_err(|error| error.into_io_error())
 }
 
+/// Returns the longest common ancestor of all the given paths.
+///
+/// Paths are compared component by component, as with [`Utf8Path::starts_with`]. A path is
+/// considered to be an ancestor of itself, so for a single path, the result is that path.
+///
+/// This is a purely lexical operation, and does not access the filesystem or resolve symlinks.
+/// In most cases, paths should be [normalized](Utf8Path::normalize_lexically) first.
+///
+/// Returns [`None`] if:
+///
+/// * `paths` is empty;
+/// * some of the paths are absolute while others are relative, or if they have different roots
+///   or prefixes (e.g. `C:\` and `D:\` on Windows).
+///
+/// Relative paths with no components in common have the empty path as their common ancestor.
+///
+/// # Examples
+///
+/// ```
+/// use camino::{Utf8Path, Utf8PathBuf};
+///
+/// let manifests = [
+///     Utf8Path::new("/ws/crates/foo/Cargo.toml"),
+///     Utf8Path::new("/ws/crates/bar/Cargo.toml"),
+///     Utf8Path::new("/ws/Cargo.toml"),
+/// ];
+/// assert_eq!(camino::common_ancestor(manifests).unwrap(), Utf8Path::new("/ws"));
+///
+/// let files = vec![Utf8PathBuf::from("src/lib.rs"), Utf8PathBuf::from("src/bin/main.rs")];
+/// assert_eq!(camino::common_ancestor(&files).unwrap(), Utf8Path::new("src"));
+///
+/// assert_eq!(camino::common_ancestor(["src", "tests"]).unwrap(), Utf8Path::new(""));
+///
+/// // Absolute and relative paths have no common ancestor.
+/// assert_eq!(camino::common_ancestor(["/ws/src", "src"]), None);
+/// assert_eq!(camino::common_ancestor(Vec::<&str>::new()), None);
+/// ```
+pub fn common_ancestor<I>(paths: I) -> Option<Utf8PathBuf>
+where
+    I: IntoIterator,
+    I::Item: AsRef<Utf8Path>,
+{
+    // The number of leading prefix and root components of a path, which must be shared for paths
+    // to have a common ancestor.
+    fn anchor_len(path: &Utf8Path) -> usize {
+        path.components()
+            .take_while(|c| matches!(c, Utf8Component::Prefix(_) | Utf8Component::RootDir))
+            .count()
+    }
+
+    fn components(path: &Utf8Path) -> impl Iterator<Item = Utf8Component<'_>> {
+        path.components().filter(|c| *c != Utf8Component::CurDir)
+    }
+
+    let mut paths = paths.into_iter();
+    let first = paths.next()?;
+    let first = first.as_ref();
+    let first_anchor_len = anchor_len(first);
+    let mut shared = components(first).count();
+
+    for path in paths {
+        let path = path.as_ref();
+        let matching = components(first)
+            .zip(components(path))
+            .take(shared)
+            .take_while(|(a, b)| a == b)
+            .count();
+        if matching < first_anchor_len || matching < anchor_len(path) {
+            return None;
+        }
+        shared = matching;
+    }
+
+    Some(components(first).take(shared).collect())
+}
+
+/// Creates a [`Utf8PathBuf`] by joining together a list of path components.
+///
+/// Each argument can be any expression whose type implements [`AsRef<Utf8Path>`], such as a
+/// string literal, a [`String`], a [`Utf8Path`] or a [`Utf8PathBuf`]. The arguments are
+/// [pushed](Utf8PathBuf::push) in order onto an empty [`Utf8PathBuf`], so an argument that is an
+/// absolute path replaces everything before it.
+///
+/// With no arguments, this creates an empty [`Utf8PathBuf`].
+///
+/// The path is built at runtime and allocates, so this macro can't be used to initialize a
+/// `const` or `static`. There is currently no way to create a `&'static Utf8Path` in a const
+/// context either, since [`Path::new`] isn't a `const fn`. For a `static`, use
+/// [`Utf8Path::new`] in a lazily initialized value such as `std::sync::LazyLock`.
+///
+/// # Examples
+///
+/// ```
+/// use camino::{utf8_path, Utf8Path, Utf8PathBuf};
+///
+/// let target_dir = Utf8Path::new("target");
+/// let profile = String::from("debug");
+///
+/// let path = utf8_path!(target_dir, profile, "build", "out.txt");
+/// assert_eq!(path, Utf8PathBuf::from("target/debug/build/out.txt"));
+///
+/// let path = utf8_path!("/usr", "lib", "/etc");
+/// assert_eq!(path, Utf8PathBuf::from("/etc"));
+///
+/// assert_eq!(utf8_path!(), Utf8PathBuf::new());
+/// ```
+#[macro_export]
+macro_rules! utf8_path {
+    ($($component:expr),* $(,)?) => {{
+        #[allow(unused_mut)]
+        let mut path = $crate::Utf8PathBuf::new();
+        $(path.push($component);)*
+        path
+    }};
+}
+
+// Checks that component can be inserted into the middle of a path, i.e. that it isn't empty and
+// doesn't have a prefix or root.
+fn check_inserted_component(component: &str) -> &str {
+    assert!(!component.is_empty(), "cannot insert an empty component");
+    if let Some(Utf8Component::Prefix(_) | Utf8Component::RootDir) =
+        Utf8Path::new(component).components().next()
+    {
+        panic!("cannot insert a component with a prefix or root: {component}");
+    }
+    component
+}
+
+// Removes the component at index from s, given the ranges of all components in s. If the component
+// is the last one, the separators before it are removed, otherwise the separators after it are.
+fn remove_component_range(s: &mut String, ranges: &[Range<usize>], index: usize, is_last: bool) {
+    if !is_last {
+        s.replace_range(ranges[index].start..ranges[index + 1].start, "");
+    } else if index == 0 {
+        s.clear();
+    } else {
+        s.truncate(ranges[index - 1].end);
+    }
+}
+
+// Splits a file name into its prefix and extensions, at the first `.` that isn't at the
+// beginning. This matches the behavior of Path::file_prefix in the standard library.
+fn split_file_at_dot(file_name: &str) -> (&str, Option<&str>) {
+    if file_name == ".." {
+        return (file_name, None);
+    }
+    // Skipping the first byte is fine because we only look for ASCII `.` characters, which are
+    // always on a char boundary.
+    match file_name.bytes().skip(1).position(|b| b == b'.') {
+        Some(i) => (&file_name[..i + 1], Some(&file_name[i + 2..])),
+        None => (file_name, None),
+    }
+}
+
 // invariant: OsStr must be guaranteed to be utf8 data
 #[inline]
 unsafe fn str_assume_utf8(string: &OsStr) -> &str {
@@ -0,0 +1,522 @@
+// Copyright (c) The camino Contributors
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Checks for paths that can't be used on some platforms, and sanitization of file names.
+
+use crate::{Utf8Component, Utf8Path};
+use std::fmt;
+use unicode_segmentation::UnicodeSegmentation;
+
+impl Utf8Path {
+    /// Checks whether the path can be used on each of the given `targets`, returning the problems
+    /// that were found.
+    ///
+    /// This is a purely lexical check that doesn't access the filesystem, so it can be run on one
+    /// platform to find paths that would break on another. The following problems are reported:
+    ///
+    /// * On Windows, names reserved for devices such as `CON`, `NUL` or `COM1`, with or without
+    ///   an extension ([`PortabilityIssueKind::ReservedName`]).
+    /// * On Windows, the characters `<>:"|?*` within a file name, as well as `\`, which can be
+    ///   part of a file name on other platforms but is a separator on Windows
+    ///   ([`PortabilityIssueKind::ForbiddenChar`]).
+    /// * On Windows, file names ending with `.` or a space
+    ///   ([`PortabilityIssueKind::TrailingDotOrSpace`]).
+    /// * Control characters: U+0000 through U+001F on Windows, and U+0000 on all platforms
+    ///   ([`PortabilityIssueKind::ControlChar`]).
+    /// * File names that are longer than `NAME_MAX`: 255 UTF-16 code units on Windows, and 255
+    ///   bytes on other platforms ([`PortabilityIssueKind::NameTooLong`]).
+    /// * Paths that are longer than `MAX_PATH` or `PATH_MAX`: 259 UTF-16 code units on Windows,
+    ///   1023 bytes on macOS and 4095 bytes on Linux. These limits leave room for the terminating
+    ///   NUL ([`PortabilityIssueKind::PathTooLong`]).
+    /// * On platforms other than Windows, paths that contain both `/` and `\`. Windows treats
+    ///   both as separators, while other platforms treat `\` as part of a file name
+    ///   ([`PortabilityIssueKind::MixedSeparators`]).
+    ///
+    /// Checks on file names apply to [normal](Utf8Component::Normal) components, and so don't flag
+    /// prefixes such as `C:`. Relative paths are checked as-is: note that a relative path that is
+    /// within the length limits may exceed them once it's joined onto a base directory.
+    ///
+    /// Issues are returned grouped by target, in the order the targets are given. Within each
+    /// target, issues are in the order they appear in the path.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use camino::{PortabilityIssueKind, PortabilityTarget, Utf8Path};
+    ///
+    /// let path = Utf8Path::new("docs/aux.md");
+    /// let issues = path.check_portable(PortabilityTarget::ALL);
+    /// assert_eq!(issues.len(), 1);
+    /// assert_eq!(issues[0].target(), PortabilityTarget::Windows);
+    /// assert_eq!(
+    ///     issues[0].kind(),
+    ///     &PortabilityIssueKind::ReservedName { name: "aux.md".to_owned() },
+    /// );
+    /// assert_eq!(issues[0].to_string(), "reserved file name `aux.md` (on Windows)");
+    ///
+    /// // Targets that aren't requested aren't checked.
+    /// assert!(path.check_portable(&[PortabilityTarget::Linux]).is_empty());
+    /// ```
+    #[must_use]
+    pub fn check_portable(&self, targets: &[PortabilityTarget]) -> Vec<PortabilityIssue> {
+        let mut issues = Vec::new();
+        for (i, &target) in targets.iter().enumerate() {
+            // Skip targets that were already checked.
+            if targets[..i].contains(&target) {
+                continue;
+            }
+            check_target(self, target, &mut issues);
+        }
+        issues
+    }
+}
+
+/// A platform that [`Utf8Path::check_portable`] checks paths against.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[non_exhaustive]
+pub enum PortabilityTarget {
+    /// Windows.
+    Windows,
+
+    /// macOS.
+    MacOs,
+
+    /// Linux.
+    Linux,
+}
+
+impl PortabilityTarget {
+    /// All supported targets.
+    pub const ALL: &'static [PortabilityTarget] = &[
+        PortabilityTarget::Windows,
+        PortabilityTarget::MacOs,
+        PortabilityTarget::Linux,
+    ];
+
+    // The maximum length of a file name, in the units returned by str_len.
+    fn name_max(self) -> usize {
+        255
+    }
+
+    // The maximum length of a path, excluding the terminating NUL, in the units returned by
+    // str_len.
+    fn path_max(self) -> usize {
+        match self {
+            PortabilityTarget::Windows => 259,
+            PortabilityTarget::MacOs => 1023,
+            PortabilityTarget::Linux => 4095,
+        }
+    }
+
+    // Returns the length of s as measured by this target: UTF-16 code units on Windows, and bytes
+    // elsewhere.
+    fn str_len(self, s: &str) -> usize {
+        match self {
+            PortabilityTarget::Windows => s.encode_utf16().count(),
+            PortabilityTarget::MacOs | PortabilityTarget::Linux => s.len(),
+        }
+    }
+}
+
+impl fmt::Display for PortabilityTarget {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PortabilityTarget::Windows => write!(f, "Windows"),
+            PortabilityTarget::MacOs => write!(f, "macOS"),
+            PortabilityTarget::Linux => write!(f, "Linux"),
+        }
+    }
+}
+
+/// A problem found by [`Utf8Path::check_portable`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PortabilityIssue {
+    target: PortabilityTarget,
+    kind: PortabilityIssueKind,
+}
+
+impl PortabilityIssue {
+    /// Returns the target on which the path can't be used.
+    #[inline]
+    #[must_use]
+    pub fn target(&self) -> PortabilityTarget {
+        self.target
+    }
+
+    /// Returns the kind of problem that was found.
+    #[inline]
+    #[must_use]
+    pub fn kind(&self) -> &PortabilityIssueKind {
+        &self.kind
+    }
+}
+
+impl fmt::Display for PortabilityIssue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} (on {})", self.kind, self.target)
+    }
+}
+
+/// The kind of a [`PortabilityIssue`].
+///
+/// Lengths are in UTF-16 code units for [`PortabilityTarget::Windows`], and in bytes for other
+/// targets.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum PortabilityIssueKind {
+    /// A file name is reserved for a device, such as `CON` or `nul.txt`.
+    ReservedName {
+        /// The file name.
+        name: String,
+    },
+
+    /// A file name contains a character that isn't allowed.
+    ForbiddenChar {
+        /// The file name.
+        name: String,
+        /// The character.
+        ch: char,
+    },
+
+    /// A file name contains a control character.
+    ControlChar {
+        /// The file name.
+        name: String,
+        /// The control character.
+        ch: char,
+    },
+
+    /// A file name ends with `.` or a space, which is silently removed.
+    TrailingDotOrSpace {
+        /// The file name.
+        name: String,
+    },
+
+    /// A file name is too long.
+    NameTooLong {
+        /// The file name.
+        name: String,
+        /// The length of the file name.
+        len: usize,
+        /// The maximum allowed length.
+        max: usize,
+    },
+
+    /// The path is too long.
+    PathTooLong {
+        /// The length of the path.
+        len: usize,
+        /// The maximum allowed length.
+        max: usize,
+    },
+
+    /// The path contains both `/` and `\` characters.
+    MixedSeparators,
+}
+
+impl fmt::Display for PortabilityIssueKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PortabilityIssueKind::ReservedName { name } => {
+                write!(f, "reserved file name `{name}`")
+            }
+            PortabilityIssueKind::ForbiddenChar { name, ch } => {
+                write!(f, "file name `{name}` contains forbidden character {ch:?}")
+            }
+            PortabilityIssueKind::ControlChar { name, ch } => {
+                write!(
+                    f,
+                    "file name `{}` contains control character {ch:?}",
+                    name.escape_debug()
+                )
+            }
+            PortabilityIssueKind::TrailingDotOrSpace { name } => {
+                write!(f, "file name `{name}` ends with a dot or space")
+            }
+            PortabilityIssueKind::NameTooLong { name, len, max } => {
+                write!(f, "file name `{name}` is too long ({len} > {max})")
+            }
+            PortabilityIssueKind::PathTooLong { len, max } => {
+                write!(f, "path is too long ({len} > {max})")
+            }
+            PortabilityIssueKind::MixedSeparators => {
+                write!(f, "path contains both `/` and `\\` separators")
+            }
+        }
+    }
+}
+
+fn check_target(path: &Utf8Path, target: PortabilityTarget, issues: &mut Vec<PortabilityIssue>) {
+    let s = path.as_str();
+    let mut push = |kind| issues.push(PortabilityIssue { target, kind });
+    let windows = target == PortabilityTarget::Windows;
+
+    if !windows && s.contains('/') && s.contains('\\') {
+        push(PortabilityIssueKind::MixedSeparators);
+    }
+
+    for component in path.components() {
+        let name = match component {
+            Utf8Component::Normal(name) => name,
+            _ => continue,
+        };
+
+        if windows && is_reserved_name(name) {
+            push(PortabilityIssueKind::ReservedName {
+                name: name.to_owned(),
+            });
+        }
+        for ch in name.chars() {
+            // On platforms where `\` isn't a separator, it's part of the file name here, but
+            // would split it in two on Windows.
+            if windows && (ch == '\\' || is_windows_forbidden_char(ch)) {
+                push(PortabilityIssueKind::ForbiddenChar {
+                    name: name.to_owned(),
+                    ch,
+                });
+            } else if ch == '\0' || (windows && ch.is_ascii_control() && ch != '\x7f') {
+                push(PortabilityIssueKind::ControlChar {
+                    name: name.to_owned(),
+                    ch,
+                });
+            }
+        }
+        if windows && (name.ends_with('.') || name.ends_with(' ')) {
+            push(PortabilityIssueKind::TrailingDotOrSpace {
+                name: name.to_owned(),
+            });
+        }
+
+        let len = target.str_len(name);
+        if len > target.name_max() {
+            push(PortabilityIssueKind::NameTooLong {
+                name: name.to_owned(),
+                len,
+                max: target.name_max(),
+            });
+        }
+    }
+
+    let len = target.str_len(s);
+    if len > target.path_max() {
+        push(PortabilityIssueKind::PathTooLong {
+            len,
+            max: target.path_max(),
+        });
+    }
+}
+
+// Returns true if ch isn't allowed in file names on Windows, other than control characters and
+// separators.
+fn is_windows_forbidden_char(ch: char) -> bool {
+    matches!(ch, '<' | '>' | ':' | '"' | '|' | '?' | '*')
+}
+
+// Returns true if name is reserved for a device on Windows. Windows ignores everything after the
+// first dot, as well as trailing spaces before it, so `nul.txt` and `nul .tar.gz` are reserved too.
+// COM0 and LPT0 are listed as reserved in Microsoft's documentation and rejected by newer versions
+// of Windows.
+fn is_reserved_name(name: &str) -> bool {
+    let stem = name.split('.').next().unwrap_or(name).trim_end_matches(' ');
+    let mut chars = stem.chars();
+    let base: String = chars
+        .by_ref()
+        .take(3)
+        .map(|c| c.to_ascii_uppercase())
+        .collect();
+    let rest = chars.as_str();
+    match base.as_str() {
+        "CON" | "PRN" | "AUX" | "NUL" => rest.is_empty(),
+        "COM" | "LPT" => {
+            let mut digits = rest.chars();
+            matches!(
+                (digits.next(), digits.next()),
+                (Some('0'..='9' | '¹' | '²' | '³'), None)
+            )
+        }
+        _ => false,
+    }
+}
+
+/// Turns an arbitrary string, such as a user-supplied title, into a file name that's safe to use
+/// on all platforms.
+///
+/// The returned name is a single [normal](Utf8Component::Normal) component that passes
+/// [`Utf8Path::check_portable`] for all targets. It is produced as follows:
+///
+/// 1. Separators (`/` and `\`), control characters and the characters `<>:"|?*` are replaced
+///    with the [replacement character](SanitizeOptions::replacement).
+/// 2. If [enabled](SanitizeOptions::strip_leading_dots), leading dots are removed, so that the
+///    result isn't a hidden file on Unix.
+/// 3. If the name is longer than the [maximum length](SanitizeOptions::max_len) in bytes, it is
+///    truncated. The extension after the last dot is kept if there's room for it, and the part
+///    before it is shortened instead. Truncation never splits an [extended grapheme cluster], so
+///    e.g. a character stays together with the combining marks that follow it.
+/// 4. Trailing dots and spaces are removed. This also turns `.` and `..` into an empty name.
+/// 5. An empty name is replaced with the replacement character, and names reserved on Windows,
+///    such as `CON` or `nul.txt`, are prefixed with it.
+///
+/// [extended grapheme cluster]: https://www.unicode.org/reports/tr29/#Grapheme_Cluster_Boundaries
+///
+/// # Examples
+///
+/// ```
+/// use camino::{SanitizeOptions, Utf8Path};
+///
+/// let options = SanitizeOptions::new();
+/// assert_eq!(camino::sanitize_file_name("Q3 report: draft?", options), "Q3 report_ draft_");
+/// assert_eq!(camino::sanitize_file_name("../../etc/passwd", options), ".._.._etc_passwd");
+/// assert_eq!(camino::sanitize_file_name("..", options), "_");
+/// assert_eq!(camino::sanitize_file_name("con.txt", options), "_con.txt");
+///
+/// let options = SanitizeOptions::new().strip_leading_dots(true).max_len(12);
+/// assert_eq!(camino::sanitize_file_name(".bashrc", options), "bashrc");
+/// assert_eq!(camino::sanitize_file_name("quarterly results.pdf", options), "quarterl.pdf");
+///
+/// // The result can be used with set_file_name without escaping the parent directory.
+/// let mut path = Utf8Path::new("downloads/file").to_path_buf();
+/// path.set_file_name(camino::sanitize_file_name("../../etc/passwd", options));
+/// assert_eq!(path, "downloads/_.._etc_pass");
+/// ```
+#[must_use]
+pub fn sanitize_file_name(name: &str, options: SanitizeOptions) -> String {
+    let replacement = options.replacement;
+    let mut sanitized: String = name
+        .chars()
+        .map(|ch| {
+            if ch == '/' || ch == '\\' || ch.is_control() || is_windows_forbidden_char(ch) {
+                replacement
+            } else {
+                ch
+            }
+        })
+        .collect();
+
+    if options.strip_leading_dots {
+        let dots = sanitized.len() - sanitized.trim_start_matches('.').len();
+        sanitized.drain(..dots);
+    }
+
+    truncate_file_name(&mut sanitized, options.max_len);
+    sanitized.truncate(sanitized.trim_end_matches(['.', ' ']).len());
+
+    if sanitized.is_empty() {
+        sanitized.push(replacement);
+    } else if is_reserved_name(&sanitized) {
+        sanitized.insert(0, replacement);
+        truncate_file_name(&mut sanitized, options.max_len);
+        sanitized.truncate(sanitized.trim_end_matches(['.', ' ']).len());
+    }
+    sanitized
+}
+
+/// Options for [`sanitize_file_name`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct SanitizeOptions {
+    replacement: char,
+    strip_leading_dots: bool,
+    max_len: usize,
+}
+
+impl SanitizeOptions {
+    /// Creates the default set of options.
+    ///
+    /// Disallowed characters are replaced with `_`, leading dots are kept, and names are limited
+    /// to 255 bytes.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            replacement: '_',
+            strip_leading_dots: false,
+            max_len: 255,
+        }
+    }
+
+    /// Sets the character that disallowed characters are replaced with.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `replacement` isn't an ASCII character that is allowed in file names, or if it's
+    /// `.` or a space.
+    #[must_use]
+    pub const fn replacement(self, replacement: char) -> Self {
+        assert!(
+            replacement.is_ascii_graphic()
+                && !matches!(
+                    replacement,
+                    '.' | '/' | '\\' | '<' | '>' | ':' | '"' | '|' | '?' | '*'
+                ),
+            "replacement must be an ASCII character that is allowed in file names"
+        );
+        Self {
+            replacement,
+            ..self
+        }
+    }
+
+    /// Sets whether leading dots are removed.
+    #[must_use]
+    pub const fn strip_leading_dots(self, enabled: bool) -> Self {
+        Self {
+            strip_leading_dots: enabled,
+            ..self
+        }
+    }
+
+    /// Sets the maximum length of the file name, in bytes.
+    ///
+    /// The default is 255, which is the longest file name that's portable to all targets.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `max_len` is 0 or greater than 255.
+    #[must_use]
+    pub const fn max_len(self, max_len: usize) -> Self {
+        assert!(
+            max_len > 0 && max_len <= 255,
+            "max_len must be between 1 and 255"
+        );
+        Self { max_len, ..self }
+    }
+}
+
+impl Default for SanitizeOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Truncates name to at most max_len bytes, keeping the extension after the last dot if possible.
+fn truncate_file_name(name: &mut String, max_len: usize) {
+    if name.len() <= max_len {
+        return;
+    }
+
+    // Only keep the extension if at least one character of the stem fits before it.
+    if let Some(dot) = name.rfind('.').filter(|&dot| dot > 0) {
+        let ext_len = name.len() - dot;
+        if ext_len < max_len {
+            let stem_len = floor_cluster_boundary(&name[..dot], max_len - ext_len);
+            if stem_len > 0 {
+                name.replace_range(stem_len..dot, "");
+                return;
+            }
+        }
+    }
+
+    let len = floor_cluster_boundary(name, max_len);
+    name.truncate(len);
+}
+
+// Returns the largest index no greater than max that's on an extended grapheme cluster boundary
+// in s.
+fn floor_cluster_boundary(s: &str, max: usize) -> usize {
+    if max >= s.len() {
+        return s.len();
+    }
+    s.grapheme_indices(true)
+        .map(|(i, _)| i)
+        .take_while(|&i| i <= max)
+        .last()
+        .unwrap_or(0)
+}
@@ -0,0 +1,179 @@
+// Copyright (c) The camino Contributors
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Remapping of path prefixes, similar to rustc's `--remap-path-prefix`.
+
+use crate::{Utf8Path, Utf8PathBuf};
+use std::borrow::Cow;
+
+/// A table of rules that replace path prefixes, similar to rustc's `--remap-path-prefix`.
+///
+/// Each rule maps a prefix `from` to a replacement `to`. When a path is [remapped](Self::remap),
+/// the rule with the longest `from` (in terms of [components](Utf8Path::components)) that the path
+/// starts with is applied. If several such rules are equally long, the one that was added last
+/// wins. Rules can also be applied in [reverse](Self::unmap), from `to` back to `from`.
+///
+/// Prefixes are matched component by component, as with [`Utf8Path::starts_with`]: a rule for
+/// `/home/al` doesn't apply to `/home/alice`.
+///
+/// This is useful for making build outputs reproducible, and for redacting paths such as home
+/// directories in logs and snapshot tests.
+///
+/// # Examples
+///
+/// ```
+/// use camino::{Utf8Path, Utf8PathRemapper};
+///
+/// let mut remapper = Utf8PathRemapper::new();
+/// remapper
+///     .add_rule("/home/alice", "~")
+///     .add_rule("/home/alice/src/project", "/project");
+///
+/// // The longest matching prefix wins.
+/// let path = Utf8Path::new("/home/alice/src/project/lib.rs");
+/// assert_eq!(remapper.remap(path).as_str(), "/project/lib.rs");
+/// assert_eq!(remapper.remap(Utf8Path::new("/home/alice/.cargo")).as_str(), "~/.cargo");
+///
+/// // Paths that don't match any rule are returned unchanged.
+/// assert_eq!(remapper.remap(Utf8Path::new("/usr/lib")).as_str(), "/usr/lib");
+///
+/// // Rules can be applied in reverse.
+/// assert_eq!(
+///     remapper.unmap(Utf8Path::new("/project/lib.rs")).as_str(),
+///     "/home/alice/src/project/lib.rs",
+/// );
+/// ```
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Utf8PathRemapper {
+    rules: Vec<(Utf8PathBuf, Utf8PathBuf)>,
+}
+
+impl Utf8PathRemapper {
+    /// Creates a new remapper without any rules.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a rule that maps the prefix `from` to `to`.
+    ///
+    /// Returns `self`, so that calls can be chained.
+    pub fn add_rule(
+        &mut self,
+        from: impl Into<Utf8PathBuf>,
+        to: impl Into<Utf8PathBuf>,
+    ) -> &mut Self {
+        self.rules.push((from.into(), to.into()));
+        self
+    }
+
+    /// Returns the rules in this remapper as `(from, to)` pairs, in the order they were added.
+    pub fn rules(&self) -> impl ExactSizeIterator<Item = (&Utf8Path, &Utf8Path)> + '_ {
+        self.rules
+            .iter()
+            .map(|(from, to)| (from.as_path(), to.as_path()))
+    }
+
+    /// Returns `true` if this remapper doesn't have any rules.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Applies the best matching rule to `path`, replacing its prefix `from` with `to`.
+    ///
+    /// If no rule matches, `path` is returned unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use camino::{Utf8Path, Utf8PathRemapper};
+    /// use std::borrow::Cow;
+    ///
+    /// let mut remapper = Utf8PathRemapper::new();
+    /// remapper.add_rule("/build/out", "target");
+    ///
+    /// assert_eq!(remapper.remap(Utf8Path::new("/build/out/debug")).as_str(), "target/debug");
+    /// assert!(matches!(remapper.remap(Utf8Path::new("/build/src")), Cow::Borrowed(_)));
+    /// ```
+    #[must_use]
+    pub fn remap<'a>(&self, path: &'a Utf8Path) -> Cow<'a, Utf8Path> {
+        apply(path, self.rules.iter().map(|(from, to)| (from, to)))
+    }
+
+    /// Applies the best matching rule to `path` in reverse, replacing its prefix `to` with
+    /// `from`.
+    ///
+    /// Rules are matched against their `to` side, again preferring the longest match. If no rule
+    /// matches, `path` is returned unchanged.
+    ///
+    /// Remapping and then unmapping a path yields the original path if the rules' `to` sides
+    /// don't overlap with each other or with paths that aren't remapped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use camino::{Utf8Path, Utf8PathRemapper};
+    ///
+    /// let mut remapper = Utf8PathRemapper::new();
+    /// remapper.add_rule("/home/alice", "$HOME");
+    ///
+    /// let path = Utf8Path::new("/home/alice/notes.txt");
+    /// let remapped = remapper.remap(path);
+    /// assert_eq!(remapped.as_str(), "$HOME/notes.txt");
+    /// assert_eq!(remapper.unmap(&remapped), path);
+    /// ```
+    #[must_use]
+    pub fn unmap<'a>(&self, path: &'a Utf8Path) -> Cow<'a, Utf8Path> {
+        apply(path, self.rules.iter().map(|(from, to)| (to, from)))
+    }
+}
+
+impl<F, T> Extend<(F, T)> for Utf8PathRemapper
+where
+    F: Into<Utf8PathBuf>,
+    T: Into<Utf8PathBuf>,
+{
+    fn extend<I: IntoIterator<Item = (F, T)>>(&mut self, iter: I) {
+        for (from, to) in iter {
+            self.add_rule(from, to);
+        }
+    }
+}
+
+impl<F, T> FromIterator<(F, T)> for Utf8PathRemapper
+where
+    F: Into<Utf8PathBuf>,
+    T: Into<Utf8PathBuf>,
+{
+    fn from_iter<I: IntoIterator<Item = (F, T)>>(iter: I) -> Self {
+        let mut remapper = Self::new();
+        remapper.extend(iter);
+        remapper
+    }
+}
+
+// Applies the rule with the longest matching prefix to path. Later rules win ties.
+fn apply<'a, 'r>(
+    path: &'a Utf8Path,
+    rules: impl Iterator<Item = (&'r Utf8PathBuf, &'r Utf8PathBuf)>,
+) -> Cow<'a, Utf8Path> {
+    let mut best: Option<(usize, &Utf8Path, &Utf8Path)> = None;
+    for (prefix, replacement) in rules {
+        if !path.starts_with(prefix) {
+            continue;
+        }
+        let len = prefix.components().count();
+        if best.map_or(true, |(best_len, _, _)| len >= best_len) {
+            best = Some((len, prefix, replacement));
+        }
+    }
+
+    match best {
+        Some((_, prefix, replacement)) => Cow::Owned(
+            path.rebase(prefix, replacement)
+                .expect("path starts with prefix"),
+        ),
+        None => Cow::Borrowed(path),
+    }
+}
@@ -7,7 +7,7 @@
 //! * `Utf8PathBuf` could be derived, but we don't depend on serde_derive to
 //!   improve compile times. It's also very straightforward to implement.
 
-use crate::{Utf8Path, Utf8PathBuf};
+use crate::{Utf8AnchoredPath, Utf8Path, Utf8PathBuf};
 use serde_core::{Deserialize, Deserializer, Serialize, Serializer, de};
 use std::fmt;
 
@@ -126,6 +126,17 @@ impl<'de> Deserialize<'de> for Box<Utf8Path> {
 
 // impl Serialize for Box<Utf8Path> comes from impl Serialize for Utf8Path.
 
+// Only the relative part is serialized, matching the Display impl. There's no Deserialize impl
+// since the base directory can't be recovered.
+impl Serialize for Utf8AnchoredPath {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        self.rel().serialize(serializer)
+    }
+}
+
 // Can't provide impls for Arc/Rc due to orphan rule issues, but we could provide
 // `with` impls in the future as requested.
 
@@ -215,6 +226,14 @@ mod tests {
         );
     }
 
+    #[test]
+    fn anchored_serializes_rel() {
+        let path = Utf8AnchoredPath::new("/ws", "src/lib.rs").expect("path is within base");
+        let encoded = bincode::serialize(&path).expect("encoded correctly");
+        let decoded: Utf8PathBuf = bincode::deserialize(&encoded).expect("decoded correctly");
+        assert_eq!(decoded, "src/lib.rs");
+    }
+
     #[derive(Serialize, Debug)]
     struct Encode {
         path: ByteBuf,
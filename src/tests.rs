@@ -103,3 +103,85 @@ fn test_deref_mut() {
     let mut path_buf = Utf8PathBuf::from("foobar");
     let _: &mut Utf8Path = &mut path_buf;
 }
+
+#[test]
+fn test_div() {
+    let path = Utf8Path::new("a");
+    let path_buf = Utf8PathBuf::from("a");
+    let expected = Utf8Path::new("a/b/c/d/e");
+
+    // Every combination of borrowed and owned operands works.
+    assert_eq!(
+        path / "b" / String::from("c") / Utf8Path::new("d") / Utf8PathBuf::from("e"),
+        expected,
+    );
+    assert_eq!(&path_buf / "b" / "c" / "d" / "e", expected);
+    assert_eq!(path_buf.clone() / "b" / "c" / "d" / "e", expected);
+
+    let mut div_assign = path_buf;
+    div_assign /= "b";
+    div_assign /= &Utf8PathBuf::from("c/d");
+    div_assign /= Utf8Path::new("e");
+    assert_eq!(div_assign, expected);
+}
+
+#[test]
+fn test_utf8_path_macro() {
+    let dir = Utf8PathBuf::from("target");
+    let profile = String::from("debug");
+    let name: &str = "out.txt";
+
+    // Owned and borrowed arguments can be mixed, and a trailing comma is allowed.
+    let path = crate::utf8_path!(&dir, profile.clone(), Utf8Path::new("build"), name,);
+    assert_eq!(path.as_str(), "target/debug/build/out.txt");
+    let path = crate::utf8_path!(
+        dir,
+        profile,
+        "build",
+        Box::<Utf8Path>::from(Utf8Path::new(name))
+    );
+    assert_eq!(path.as_str(), "target/debug/build/out.txt");
+
+    assert_eq!(crate::utf8_path!("a",).as_str(), "a");
+    assert_eq!(crate::utf8_path!().as_str(), "");
+}
+
+#[test]
+fn test_push_scoped() {
+    let mut path = Utf8PathBuf::from("a");
+    for pushed in ["b", "b/c", "", "/", "/b", "../b"] {
+        {
+            let mut guard = path.push_scoped(pushed);
+            assert_eq!(*guard, Utf8Path::new("a").join(pushed));
+            let nested = guard.push_scoped("d");
+            assert_eq!(*nested, Utf8Path::new("a").join(pushed).join("d"));
+        }
+        assert_eq!(path.as_str(), "a", "path restored after pushing {pushed}");
+    }
+
+    // Nested guards restore the path to exactly what it was when they were created, even if
+    // they replaced it with an absolute path.
+    {
+        let mut guard = path.push_scoped("b");
+        {
+            let mut nested = guard.push_scoped("/x");
+            assert_eq!(nested.as_str(), "/x");
+            let nested = nested.push_scoped("y");
+            assert_eq!(*nested, Utf8Path::new("/x/y"));
+        }
+        assert_eq!(*guard, Utf8Path::new("a/b"));
+    }
+    assert_eq!(path.as_str(), "a");
+
+    // Recent versions of Rust resolve `..` when pushing onto a verbatim path, so the path doesn't
+    // just get longer.
+    #[cfg(windows)]
+    {
+        let mut path = Utf8PathBuf::from(r"\\?\C:\a");
+        {
+            let guard = path.push_scoped(r"..\b");
+            assert!(guard.as_str().ends_with('b'), "{guard:?}");
+        }
+        assert_eq!(path.as_str(), r"\\?\C:\a");
+    }
+}
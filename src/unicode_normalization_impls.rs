@@ -0,0 +1,156 @@
+// Copyright (c) The camino Contributors
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Unicode normalization for [`Utf8Path`], based on the [`unicode_normalization`] crate.
+//!
+//! Different platforms store the same file name in different Unicode normalization forms. For
+//! example, macOS tends to hand out file names in NFD (decomposed) form, while most other sources,
+//! such as text typed in by users, are in NFC (composed) form. These paths look identical when
+//! displayed, but are not equal as strings.
+
+// NOTE: #[cfg(feature = "unicode-normalization01")] is specified here to work with `doc_cfg`.
+
+use crate::Utf8Path;
+use std::{borrow::Cow, cmp::Ordering};
+use unicode_normalization::{UnicodeNormalization, is_nfc, is_nfd};
+
+#[cfg(feature = "unicode-normalization01")]
+impl Utf8Path {
+    /// Returns the path converted to Unicode Normalization Form C (canonical composition).
+    ///
+    /// If the path is already in NFC, this returns it as-is without allocating.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use camino::Utf8Path;
+    ///
+    /// // "é" as "e" followed by U+0301 COMBINING ACUTE ACCENT.
+    /// let nfd = Utf8Path::new("cafe\u{301}/menu.txt");
+    /// assert_eq!(nfd.to_nfc(), Utf8Path::new("caf\u{e9}/menu.txt"));
+    /// ```
+    #[must_use]
+    pub fn to_nfc(&self) -> Cow<'_, Utf8Path> {
+        if is_nfc(self.as_str()) {
+            Cow::Borrowed(self)
+        } else {
+            Cow::Owned(self.as_str().nfc().collect::<String>().into())
+        }
+    }
+
+    /// Returns the path converted to Unicode Normalization Form D (canonical decomposition).
+    ///
+    /// If the path is already in NFD, this returns it as-is without allocating.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use camino::Utf8Path;
+    ///
+    /// let nfc = Utf8Path::new("caf\u{e9}/menu.txt");
+    /// assert_eq!(nfc.to_nfd(), Utf8Path::new("cafe\u{301}/menu.txt"));
+    /// ```
+    #[must_use]
+    pub fn to_nfd(&self) -> Cow<'_, Utf8Path> {
+        if is_nfd(self.as_str()) {
+            Cow::Borrowed(self)
+        } else {
+            Cow::Owned(self.as_str().nfd().collect::<String>().into())
+        }
+    }
+
+    /// Compares `self` and `other` for equality, treating canonically equivalent Unicode
+    /// sequences as equal.
+    ///
+    /// Paths are compared component by component, like the [`PartialEq`] implementation for
+    /// [`Utf8Path`], with each component converted to NFC first. This means that, for example,
+    /// NFC and NFD forms of the same file name compare equal.
+    ///
+    /// For a type that can be used as a key in maps and sets, see
+    /// [`Utf8PathNormalizationInsensitive`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use camino::Utf8Path;
+    ///
+    /// let nfc = Utf8Path::new("caf\u{e9}/menu.txt");
+    /// let nfd = Utf8Path::new("cafe\u{301}/menu.txt");
+    /// assert_ne!(nfc, nfd);
+    /// assert!(nfc.eq_ignore_normalization(nfd));
+    /// ```
+    #[must_use]
+    pub fn eq_ignore_normalization(&self, other: impl AsRef<Utf8Path>) -> bool {
+        self.cmp_ignore_normalization(other) == Ordering::Equal
+    }
+
+    /// Compares `self` and `other` for ordering, treating canonically equivalent Unicode
+    /// sequences as equal.
+    ///
+    /// This is consistent with [`eq_ignore_normalization`](Self::eq_ignore_normalization): paths
+    /// are compared component by component, with each component converted to NFC first.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use camino::Utf8Path;
+    /// use std::cmp::Ordering;
+    ///
+    /// let nfc = Utf8Path::new("caf\u{e9}/menu.txt");
+    /// let nfd = Utf8Path::new("cafe\u{301}/menu.txt");
+    /// assert_eq!(nfc.cmp_ignore_normalization(nfd), Ordering::Equal);
+    /// ```
+    #[must_use]
+    pub fn cmp_ignore_normalization(&self, other: impl AsRef<Utf8Path>) -> Ordering {
+        self.components()
+            .map(NfcComponent)
+            .cmp(other.as_ref().components().map(NfcComponent))
+    }
+}
+
+path_wrapper! {
+    /// A wrapper around a path that compares, orders and hashes it after Unicode normalization.
+    ///
+    /// The [`PartialEq`], [`Ord`] and [`Hash`](std::hash::Hash) implementations on [`Utf8Path`]
+    /// and [`Utf8PathBuf`](crate::Utf8PathBuf) compare paths as plain strings, so the NFC and NFD
+    /// forms of the same file name are considered different. This wrapper instead uses
+    /// [`Utf8Path::eq_ignore_normalization`] and [`Utf8Path::cmp_ignore_normalization`], and can
+    /// be used as a key in maps and sets.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use camino::{Utf8Path, Utf8PathNormalizationInsensitive};
+    /// use std::collections::HashMap;
+    ///
+    /// let mut manifest = HashMap::new();
+    /// manifest.insert(Utf8PathNormalizationInsensitive::new(Utf8Path::new("caf\u{e9}.txt")), 1);
+    ///
+    /// // A file name as returned by macOS, in NFD form.
+    /// let key = Utf8PathNormalizationInsensitive::new(Utf8Path::new("cafe\u{301}.txt"));
+    /// assert_eq!(manifest.get(&key), Some(&1));
+    /// ```
+    #[cfg(feature = "unicode-normalization01")]
+    pub struct Utf8PathNormalizationInsensitive;
+
+    /// Wraps a path so that it is compared, ordered and hashed after Unicode normalization.
+    fn new;
+
+    fn eq(a, b) { a.eq_ignore_normalization(b) }
+    fn cmp(a, b) { a.cmp_ignore_normalization(b) }
+    fn hash(path, state) {
+        for component in path.components() {
+            NfcComponent(component).hash(state);
+        }
+    }
+}
+
+mapped_component! {
+    // A path component that is compared, ordered and hashed after conversion to NFC.
+    //
+    // Separators are never combined with adjacent characters, so normalizing each component
+    // separately is the same as normalizing the whole path.
+    struct NfcComponent;
+
+    fn chars(s) { s.nfc() }
+}
@@ -0,0 +1,168 @@
+// Copyright (c) The camino Contributors
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Macros shared by the wrapper types that compare paths in alternative ways, such as
+//! [`Utf8PathCaseInsensitive`](crate::Utf8PathCaseInsensitive).
+
+// Defines a wrapper type around `P: AsRef<Utf8Path>` that compares, orders and hashes the path
+// using the given functions.
+//
+// The functions are given the wrapped paths as `&Utf8Path`. `eq` and `cmp` must be consistent with
+// each other, and `hash` must be consistent with `eq`.
+macro_rules! path_wrapper {
+    (
+        $(#[$attr:meta])*
+        pub struct $name:ident;
+
+        $(#[$new_attr:meta])*
+        fn new;
+
+        fn eq($eq_a:ident, $eq_b:ident) $eq:block
+        fn cmp($cmp_a:ident, $cmp_b:ident) $cmp:block
+        fn hash($hash_path:ident, $hash_state:ident) $hash:block
+    ) => {
+        $(#[$attr])*
+        #[derive(Clone, Copy, Default)]
+        pub struct $name<P>(P);
+
+        impl<P> $name<P> {
+            $(#[$new_attr])*
+            ///
+            /// `P` can be any type that implements `AsRef<Utf8Path>`, e.g. `&Utf8Path` or
+            /// [`Utf8PathBuf`](crate::Utf8PathBuf).
+            #[inline]
+            pub const fn new(path: P) -> Self {
+                Self(path)
+            }
+
+            /// Returns the wrapped path.
+            #[inline]
+            pub fn into_inner(self) -> P {
+                self.0
+            }
+        }
+
+        impl<P: AsRef<$crate::Utf8Path>> $name<P> {
+            /// Returns the wrapped path as a [`Utf8Path`](crate::Utf8Path).
+            #[inline]
+            pub fn as_path(&self) -> &$crate::Utf8Path {
+                self.0.as_ref()
+            }
+        }
+
+        impl<P: AsRef<$crate::Utf8Path>> AsRef<$crate::Utf8Path> for $name<P> {
+            #[inline]
+            fn as_ref(&self) -> &$crate::Utf8Path {
+                self.as_path()
+            }
+        }
+
+        impl<P: AsRef<$crate::Utf8Path>> ::std::fmt::Debug for $name<P> {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+                f.debug_tuple(stringify!($name))
+                    .field(&self.as_path())
+                    .finish()
+            }
+        }
+
+        impl<P: AsRef<$crate::Utf8Path>> ::std::fmt::Display for $name<P> {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+                ::std::fmt::Display::fmt(self.as_path(), f)
+            }
+        }
+
+        impl<P: AsRef<$crate::Utf8Path>, Q: AsRef<$crate::Utf8Path>> PartialEq<$name<Q>>
+            for $name<P>
+        {
+            #[inline]
+            fn eq(&self, other: &$name<Q>) -> bool {
+                let ($eq_a, $eq_b) = (self.as_path(), other.as_path());
+                $eq
+            }
+        }
+
+        impl<P: AsRef<$crate::Utf8Path>> Eq for $name<P> {}
+
+        impl<P: AsRef<$crate::Utf8Path>, Q: AsRef<$crate::Utf8Path>> PartialOrd<$name<Q>>
+            for $name<P>
+        {
+            #[inline]
+            fn partial_cmp(&self, other: &$name<Q>) -> Option<::std::cmp::Ordering> {
+                let ($cmp_a, $cmp_b) = (self.as_path(), other.as_path());
+                Some($cmp)
+            }
+        }
+
+        impl<P: AsRef<$crate::Utf8Path>> Ord for $name<P> {
+            #[inline]
+            fn cmp(&self, other: &Self) -> ::std::cmp::Ordering {
+                let ($cmp_a, $cmp_b) = (self.as_path(), other.as_path());
+                $cmp
+            }
+        }
+
+        impl<P: AsRef<$crate::Utf8Path>> ::std::hash::Hash for $name<P> {
+            fn hash<H: ::std::hash::Hasher>(&self, state: &mut H) {
+                let ($hash_path, $hash_state) = (self.as_path(), state);
+                $hash
+            }
+        }
+    };
+}
+
+// Defines a path component type that is compared, ordered and hashed by mapping its characters,
+// e.g. through case folding.
+//
+// `chars` is given the component as `&str`, and returns an iterator over the mapped characters.
+// Components are first ordered by kind, and then by their mapped characters.
+macro_rules! mapped_component {
+    (
+        struct $name:ident;
+
+        fn chars($s:ident) $chars:block
+    ) => {
+        struct $name<'a>($crate::Utf8Component<'a>);
+
+        impl $name<'_> {
+            fn mapped_chars(&self) -> impl Iterator<Item = char> + '_ {
+                let $s = self.0.as_str();
+                $chars
+            }
+        }
+
+        impl PartialEq for $name<'_> {
+            fn eq(&self, other: &Self) -> bool {
+                self.cmp(other) == ::std::cmp::Ordering::Equal
+            }
+        }
+
+        impl Eq for $name<'_> {}
+
+        impl PartialOrd for $name<'_> {
+            fn partial_cmp(&self, other: &Self) -> Option<::std::cmp::Ordering> {
+                Some(self.cmp(other))
+            }
+        }
+
+        impl Ord for $name<'_> {
+            fn cmp(&self, other: &Self) -> ::std::cmp::Ordering {
+                self.0
+                    .kind_rank()
+                    .cmp(&other.0.kind_rank())
+                    .then_with(|| self.mapped_chars().cmp(other.mapped_chars()))
+            }
+        }
+
+        impl ::std::hash::Hash for $name<'_> {
+            fn hash<H: ::std::hash::Hasher>(&self, state: &mut H) {
+                state.write_u8(self.0.kind_rank());
+                for c in self.mapped_chars() {
+                    state.write_u32(c as u32);
+                }
+                // Terminate the component so that e.g. ["ab", "c"] and ["a", "bc"] hash
+                // differently.
+                state.write_u32(u32::MAX);
+            }
+        }
+    };
+}
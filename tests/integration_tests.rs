@@ -1,9 +1,15 @@
 // Copyright (c) The camino Contributors
 // SPDX-License-Identifier: MIT OR Apache-2.0
 
-use camino::{Utf8Path, Utf8PathBuf};
+use camino::{
+    ExpandKey, ExpandOptions, PortabilityIssueKind, PortabilityTarget, SanitizeOptions,
+    Utf8AnchoredPath, Utf8Component, Utf8Path, Utf8PathBuf, Utf8PathCaseInsensitive, Utf8PathKind,
+    Utf8PathRemapper,
+};
 use std::{
+    cmp::Ordering,
     collections::hash_map::DefaultHasher,
+    ffi::OsString,
     hash::{Hash, Hasher},
     path::Path,
 };
@@ -38,6 +44,13 @@ static PATH_CORPUS: &[&str] = &[
     "\\\\server\\foo\\bar",
     #[cfg(windows)]
     "\\\\.\\C:\\foo\\bar.txt",
+    // These prefixes imply a root without a separator after them.
+    #[cfg(windows)]
+    "\\\\server\\share",
+    #[cfg(windows)]
+    "\\\\?\\UNC\\server\\share",
+    #[cfg(windows)]
+    "\\\\.\\COM1",
 ];
 
 #[test]
@@ -158,3 +171,1251 @@ fn test_eq_implies_equal_hash() {
          (e.g. \"foo/bar\" / \"foo//bar\") for this test to be meaningful",
     );
 }
+
+#[test]
+fn test_normalize_lexically() {
+    for &path in PATH_CORPUS {
+        let path = Utf8Path::new(path);
+        let normalized = match path.normalize_lexically() {
+            Ok(normalized) => normalized,
+            Err(_) => {
+                assert!(
+                    path.components().any(|c| c == Utf8Component::ParentDir),
+                    "only paths with `..` can fail to normalize: {path}",
+                );
+                continue;
+            }
+        };
+
+        assert!(
+            normalized
+                .components()
+                .all(|c| !matches!(c, Utf8Component::CurDir | Utf8Component::ParentDir)),
+            "normalized path {normalized} for {path} has no `.` or `..` components",
+        );
+        assert_eq!(
+            normalized.has_root(),
+            path.has_root(),
+            "normalizing {path} preserves the root",
+        );
+        assert_eq!(
+            normalized.normalize_lexically().as_ref(),
+            Ok(&normalized),
+            "normalizing {path} is idempotent",
+        );
+    }
+}
+
+#[test]
+fn test_relative_to() {
+    // The prefix and root components of a path, which relative paths can't bridge.
+    fn anchor(path: &Utf8Path) -> Vec<Utf8Component<'_>> {
+        path.components()
+            .take_while(|c| matches!(c, Utf8Component::Prefix(_) | Utf8Component::RootDir))
+            .collect()
+    }
+
+    for &path in PATH_CORPUS {
+        for &base in PATH_CORPUS {
+            let (path, base) = match (
+                Utf8Path::new(path).normalize_lexically(),
+                Utf8Path::new(base).normalize_lexically(),
+            ) {
+                (Ok(path), Ok(base)) => (path, base),
+                _ => continue,
+            };
+
+            match path.relative_to(&base) {
+                Some(relative) => {
+                    assert!(
+                        anchor(&relative).is_empty(),
+                        "relative path from {base} to {path} is relative: {relative}",
+                    );
+                    assert_eq!(
+                        base.join(&relative).normalize_lexically().as_ref(),
+                        Ok(&path),
+                        "joining {relative} onto {base} yields {path}",
+                    );
+                }
+                None => {
+                    assert_ne!(
+                        anchor(&path),
+                        anchor(&base),
+                        "normalized paths with the same root are always related: {path} and {base}",
+                    );
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn test_join_within() {
+    for &root in PATH_CORPUS {
+        for &path in PATH_CORPUS {
+            let root = Utf8Path::new(root);
+            let path = Utf8Path::new(path);
+            let escapes = path
+                .components()
+                .any(|c| matches!(c, Utf8Component::Prefix(_) | Utf8Component::RootDir))
+                || path.normalize_lexically().is_err();
+
+            match root.join_within(path) {
+                Ok(joined) => {
+                    assert!(!escapes, "joining {path} within {root} should fail");
+                    assert!(
+                        joined.starts_with(root),
+                        "joining {path} within {root} stays within it: {joined}",
+                    );
+                }
+                Err(error) => {
+                    assert!(escapes, "joining {path} within {root} should succeed");
+                    assert_eq!(error.as_path(), path);
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn test_common_ancestor() {
+    // The prefix and root components of a path, which must be the same for paths to have a
+    // common ancestor.
+    fn anchor(path: &Utf8Path) -> Vec<Utf8Component<'_>> {
+        path.components()
+            .take_while(|c| matches!(c, Utf8Component::Prefix(_) | Utf8Component::RootDir))
+            .collect()
+    }
+
+    fn components(path: &Utf8Path) -> Vec<Utf8Component<'_>> {
+        path.components()
+            .filter(|c| *c != Utf8Component::CurDir)
+            .collect()
+    }
+
+    let extra = [
+        "foo/bar/qux",
+        "/foo/baz",
+        #[cfg(windows)]
+        "D:\\foo",
+        #[cfg(windows)]
+        "c:\\foo\\baz",
+        #[cfg(windows)]
+        "\\\\other\\foo\\bar",
+    ];
+    let paths: Vec<_> = PATH_CORPUS
+        .iter()
+        .chain(&extra)
+        .map(Utf8Path::new)
+        .collect();
+
+    for &a in &paths {
+        for &b in &paths {
+            let ancestor = camino::common_ancestor([a, b]);
+            assert_eq!(
+                camino::common_ancestor([b, a]),
+                ancestor,
+                "common ancestor of {a} and {b} doesn't depend on the order",
+            );
+            if anchor(a) != anchor(b) {
+                assert_eq!(ancestor, None, "{a} and {b} have different roots");
+                continue;
+            }
+
+            let ancestor = ancestor
+                .unwrap_or_else(|| panic!("{a} and {b} with the same root have an ancestor"));
+            let (a_components, b_components) = (components(a), components(b));
+            let len = ancestor.components().count();
+            assert_eq!(
+                a_components[..len],
+                ancestor.components().collect::<Vec<_>>(),
+                "{ancestor} is an ancestor of {a}",
+            );
+            assert_eq!(
+                b_components[..len],
+                a_components[..len],
+                "{ancestor} is an ancestor of {b}",
+            );
+            assert!(
+                a_components.len() == len
+                    || b_components.len() == len
+                    || a_components[len] != b_components[len],
+                "{ancestor} is the longest common ancestor of {a} and {b}",
+            );
+        }
+    }
+
+    // A mix of relative and absolute paths has no common ancestor, even if the paths share
+    // components.
+    assert_eq!(
+        camino::common_ancestor(["foo/bar", "foo", "/foo/bar"]),
+        None
+    );
+    assert_eq!(camino::common_ancestor(["/foo/bar", "/foo", "foo"]), None);
+    #[cfg(windows)]
+    {
+        // So do paths with different prefixes, or with and without a root after the prefix.
+        assert_eq!(camino::common_ancestor(["C:\\foo", "D:\\foo"]), None);
+        assert_eq!(camino::common_ancestor(["C:\\foo", "C:foo"]), None);
+        assert_eq!(
+            camino::common_ancestor(["\\\\server\\share\\foo", "\\\\server\\other\\foo"]),
+            None,
+        );
+        assert_eq!(
+            camino::common_ancestor(["c:\\foo\\bar", "C:\\foo\\baz"]),
+            Some(Utf8PathBuf::from("c:\\foo")),
+        );
+    }
+}
+
+#[test]
+fn test_to_slash_from_slash() {
+    for &path in PATH_CORPUS {
+        let path = Utf8Path::new(path);
+        let slash = path.to_slash();
+        assert!(
+            !slash
+                .chars()
+                .any(|c| c != '/' && std::path::is_separator(c)),
+            "{path} converted to {slash} only has / as a separator",
+        );
+        assert_eq!(
+            slash.split('/').count(),
+            path.as_str().split(std::path::is_separator).count(),
+            "{path} converted to {slash} has the same separators",
+        );
+
+        let round_tripped = Utf8PathBuf::from_slash(&slash);
+        assert_eq!(round_tripped, path, "{path} round-trips through {slash}");
+        assert_eq!(
+            round_tripped.to_slash(),
+            slash,
+            "{path} round-trips through {slash}"
+        );
+    }
+}
+
+#[test]
+fn test_trailing_separators() {
+    let extra = [
+        "foo/",
+        "foo//",
+        "/foo/bar//",
+        #[cfg(windows)]
+        "C:\\",
+        #[cfg(windows)]
+        "C:\\foo\\/",
+        #[cfg(windows)]
+        "\\\\server\\share\\",
+    ];
+    for &path in PATH_CORPUS.iter().chain(&extra) {
+        let path = Utf8Path::new(path);
+        let trimmed = path.trim_trailing_separators();
+        assert_eq!(trimmed, path, "trimming {path} keeps its components");
+        assert_eq!(
+            trimmed.has_root(),
+            path.has_root(),
+            "trimming {path} keeps its root"
+        );
+        assert_eq!(
+            trimmed.trim_trailing_separators().as_str(),
+            trimmed.as_str(),
+            "trimming {path} is idempotent",
+        );
+        assert!(
+            path.as_str().starts_with(trimmed.as_str()),
+            "trimming {path} only removes separators from the end: {trimmed}",
+        );
+        if path.has_root() && path.parent().is_none() {
+            assert_eq!(
+                trimmed.as_str(),
+                path.as_str(),
+                "{path} only consists of a root"
+            );
+        } else {
+            assert!(
+                !trimmed.has_trailing_separator(),
+                "{path} has no trailing separator after trimming: {trimmed}",
+            );
+        }
+        assert_eq!(
+            trimmed.eq_including_trailing_separator(path),
+            trimmed.as_str() == path.as_str(),
+            "{path} and {trimmed} only differ in trailing separators",
+        );
+    }
+
+    // Paths that only consist of a root keep it.
+    let roots = [
+        "/",
+        "///",
+        #[cfg(windows)]
+        "C:\\",
+        #[cfg(windows)]
+        "\\\\server\\share\\",
+        #[cfg(windows)]
+        "\\\\?\\C:\\",
+    ];
+    for root in roots {
+        let path = Utf8Path::new(root);
+        assert!(path.has_trailing_separator(), "{path}");
+        assert_eq!(path.trim_trailing_separators().as_str(), root);
+    }
+}
+
+#[test]
+fn test_case_insensitive() {
+    fn hash_one(x: impl Hash) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        x.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    for &path1 in PATH_CORPUS {
+        let upper = path1.to_uppercase();
+        let p1 = Utf8PathCaseInsensitive::new(Utf8Path::new(path1));
+        let upper = Utf8PathCaseInsensitive::new(Utf8Path::new(&upper));
+        assert_eq!(p1, upper, "{path1} is equal to its uppercase form");
+        assert_eq!(hash_one(p1), hash_one(upper));
+
+        for &path2 in PATH_CORPUS {
+            let p2 = Utf8PathCaseInsensitive::new(Utf8Path::new(path2));
+            // Case-insensitive comparisons must agree with case-sensitive ones on paths that
+            // don't have any letters in them.
+            if !path1.chars().chain(path2.chars()).any(char::is_alphabetic) {
+                assert_eq!(p1.cmp(&p2), Utf8Path::new(path1).cmp(Utf8Path::new(path2)));
+            }
+            if p1 == p2 {
+                assert_eq!(
+                    hash_one(p1),
+                    hash_one(p2),
+                    "equal paths must hash equally: {p1:?} vs {p2:?}",
+                );
+            }
+        }
+    }
+
+    // Characters whose simple case folding isn't the same as their lowercase form.
+    let equivalent = [
+        ("\u{b5}", "\u{39c}"),
+        ("\u{17f}", "S"),
+        ("\u{3c2}", "\u{3a3}"),
+        ("\u{1e9b}", "\u{1e60}"),
+        ("\u{1fbe}", "\u{399}"),
+        ("\u{1fd3}", "\u{390}"),
+        ("\u{1fe3}", "\u{3b0}"),
+        ("\u{fb05}", "\u{fb06}"),
+    ];
+    for (a, b) in equivalent {
+        let (a, b) = (Utf8Path::new(a), Utf8Path::new(b));
+        assert!(a.eq_ignore_case(b), "{a} and {b} are equal ignoring case");
+        assert_eq!(a.cmp_ignore_case(b), Ordering::Equal, "{a} and {b}");
+        assert_eq!(
+            hash_one(Utf8PathCaseInsensitive::new(a)),
+            hash_one(Utf8PathCaseInsensitive::new(b)),
+            "{a} and {b} hash equally ignoring case",
+        );
+    }
+}
+
+#[cfg(feature = "unicode-normalization01")]
+#[test]
+fn test_normalization_insensitive() {
+    use camino::Utf8PathNormalizationInsensitive;
+
+    fn hash_one(x: impl Hash) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        x.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    // Pairs of canonically equivalent paths, in different normalization forms.
+    static EQUIVALENT: &[(&str, &str)] = &[
+        ("caf\u{e9}/menu.txt", "cafe\u{301}/menu.txt"),
+        (
+            "\u{d55c}\u{ae00}",
+            "\u{1112}\u{1161}\u{11ab}\u{1100}\u{1173}\u{11af}",
+        ),
+        ("\u{212b}ngstr\u{f6}m", "A\u{30a}ngstro\u{308}m"),
+        // Combining marks in a different order.
+        ("s\u{323}\u{307}", "s\u{307}\u{323}"),
+        ("\u{1e69}/x", "s\u{323}\u{307}//x"),
+    ];
+
+    let corpus = PATH_CORPUS
+        .iter()
+        .copied()
+        .chain(EQUIVALENT.iter().flat_map(|&(a, b)| [a, b]));
+
+    for path1 in corpus.clone() {
+        let nfd = Utf8Path::new(path1).to_nfd();
+        let p1 = Utf8PathNormalizationInsensitive::new(Utf8Path::new(path1));
+        let nfd = Utf8PathNormalizationInsensitive::new(&*nfd);
+        assert_eq!(p1, nfd, "{path1} is equal to its NFD form");
+        assert_eq!(hash_one(p1), hash_one(nfd));
+
+        for path2 in corpus.clone() {
+            let p2 = Utf8PathNormalizationInsensitive::new(Utf8Path::new(path2));
+            // Comparisons must agree with plain ones on ASCII paths.
+            if path1.is_ascii() && path2.is_ascii() {
+                assert_eq!(p1.cmp(&p2), Utf8Path::new(path1).cmp(Utf8Path::new(path2)));
+            }
+            if p1 == p2 {
+                assert_eq!(
+                    hash_one(p1),
+                    hash_one(p2),
+                    "equal paths must hash equally: {p1:?} vs {p2:?}",
+                );
+            }
+        }
+    }
+
+    for &(a, b) in EQUIVALENT {
+        assert_ne!(Utf8Path::new(a), Utf8Path::new(b));
+        let a = Utf8PathNormalizationInsensitive::new(Utf8Path::new(a));
+        let b = Utf8PathNormalizationInsensitive::new(Utf8Path::new(b));
+        assert_eq!(a, b);
+        assert_eq!(hash_one(a), hash_one(b), "{a:?} and {b:?} hash equally");
+    }
+}
+
+#[test]
+fn test_extensions() {
+    for &path in PATH_CORPUS {
+        let path = Utf8Path::new(path);
+        let extensions: Vec<_> = path.extensions().collect();
+
+        // The last extension is always the one returned by extension().
+        assert_eq!(
+            extensions.last().copied(),
+            path.extension(),
+            "last extension of {path} matches extension()",
+        );
+
+        let stripped = path.strip_all_extensions();
+        if extensions.is_empty() {
+            assert_eq!(stripped.as_str(), path.as_str());
+        } else {
+            assert!(path.has_extension(extensions.join(".")));
+            assert_eq!(
+                format!("{stripped}.{}", extensions.join(".")),
+                path.as_str(),
+                "stripping and re-adding extensions of {path} round-trips",
+            );
+            assert_eq!(path.with_extensions(""), stripped);
+        }
+    }
+}
+
+#[test]
+fn test_slice_components() {
+    for &path in PATH_CORPUS {
+        let path = Utf8Path::new(path);
+        let components: Vec<_> = path.components().collect();
+
+        for start in 0..=components.len() {
+            for end in start..=components.len() {
+                let slice = path.slice_components(start..end);
+                assert!(
+                    slice
+                        .components()
+                        .eq(components[start..end].iter().copied()),
+                    "components {start}..{end} of {path} are {slice}",
+                );
+            }
+
+            let (head, tail) = (
+                path.slice_components(..start),
+                path.slice_components(start..),
+            );
+            assert_eq!(path.strip_suffix(tail), Some(head));
+        }
+
+        if let Some((first, rest)) = path.split_first() {
+            assert_eq!(first, components[0]);
+            assert_eq!(rest, path.slice_components(1..));
+        }
+        if let Some((last, rest)) = path.split_last() {
+            assert_eq!(last, components[components.len() - 1]);
+            assert_eq!(rest, path.slice_components(..components.len() - 1));
+        }
+    }
+}
+
+#[test]
+fn test_expand() {
+    fn lookup(key: ExpandKey<'_>) -> Option<OsString> {
+        match key {
+            ExpandKey::HomeDir => Some("/home/me".into()),
+            ExpandKey::UserHomeDir("root") => Some("/root".into()),
+            ExpandKey::Var("A") => Some("a".into()),
+            ExpandKey::Var("B_2") => Some("$A".into()),
+            ExpandKey::Var("ProgramFiles(x86)") => Some("C:\\Program Files (x86)".into()),
+            _ => None,
+        }
+    }
+
+    let all = ExpandOptions::new().percent_vars(true);
+    let cases = [
+        ("~", "/home/me"),
+        ("~/x", "/home/me/x"),
+        ("~root/x", "/root/x"),
+        ("x/~", "x/~"),
+        ("$A/$B_2", "a/$A"),
+        ("${A}b", "ab"),
+        ("$Ab", ""),
+        ("$", "$"),
+        ("$1/$-", "$1/$-"),
+        ("%A%/%B_2%", "a/$A"),
+        ("%ProgramFiles(x86)%", "C:\\Program Files (x86)"),
+        ("100%/50% off", "100%/50% off"),
+        ("%%", "%%"),
+    ];
+    for (input, expected) in cases {
+        let result = Utf8Path::new(input).expand(all, lookup);
+        if expected.is_empty() {
+            assert!(result.is_err(), "expanding {input} fails");
+        } else {
+            assert_eq!(result.unwrap(), expected, "expanding {input}");
+        }
+    }
+
+    // Disabled forms are left as-is.
+    let none = all.tilde(false).dollar_vars(false).percent_vars(false);
+    for &path in PATH_CORPUS.iter().chain(&["~/$A/%A%/${"]) {
+        assert_eq!(Utf8Path::new(path).expand(none, lookup).unwrap(), path);
+    }
+
+    let error = Utf8Path::new("~nobody").expand(all, lookup).unwrap_err();
+    assert_eq!(error.key(), Some(ExpandKey::UserHomeDir("nobody")));
+    assert_eq!(
+        error.to_string(),
+        "home directory for user `nobody` not found"
+    );
+
+    let error = Utf8Path::new("a/${A").expand(all, lookup).unwrap_err();
+    assert_eq!(error.key(), None);
+    assert_eq!(
+        error.to_string(),
+        "`${` at byte 2 must be followed by a variable name and `}`",
+    );
+    assert!(Utf8Path::new("${}").expand(all, lookup).is_err());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_expand_not_unicode() {
+    use std::{ffi::OsStr, os::unix::ffi::OsStrExt};
+
+    let value = OsStr::from_bytes(b"\xFF");
+    let error = Utf8Path::new("$A")
+        .expand(ExpandOptions::new(), |_| Some(value.to_owned()))
+        .unwrap_err();
+    assert_eq!(error.name(), Some("A"));
+    assert_eq!(error.not_unicode_value(), Some(value));
+}
+
+#[test]
+fn test_rebase_reroot() {
+    let sysroot = Utf8Path::new("sysroot");
+    for &path in PATH_CORPUS {
+        let path = Utf8Path::new(path);
+
+        // Rerooting keeps everything but the prefix and root.
+        let rerooted = path.reroot(sysroot);
+        let rest = rerooted
+            .strip_prefix(sysroot)
+            .expect("rerooted path starts with sysroot");
+        assert!(
+            rest.components().eq(path
+                .components()
+                .filter(|c| !matches!(c, Utf8Component::Prefix(_) | Utf8Component::RootDir))),
+            "{path} rerooted under {sysroot} is {rerooted}",
+        );
+
+        // Rebasing and then rebasing back yields an equal path.
+        for prefix in path.ancestors() {
+            let rebased = path.rebase(prefix, sysroot).expect("ancestor is a prefix");
+            assert_eq!(rebased.rebase(sysroot, prefix).as_deref(), Ok(path));
+        }
+
+        // Rebasing a path onto itself yields exactly the new base, without a trailing separator.
+        let rebased = path
+            .rebase(path, sysroot)
+            .expect("path is a prefix of itself");
+        assert_eq!(
+            rebased.as_str(),
+            sysroot.as_str(),
+            "{path} rebased from itself"
+        );
+    }
+
+    assert_eq!(
+        Utf8Path::new("a").rebase("a", "out").unwrap().as_str(),
+        "out"
+    );
+    assert_eq!(
+        Utf8Path::new("a/b").rebase("a/b", "/x/y").unwrap().as_str(),
+        "/x/y"
+    );
+}
+
+#[test]
+fn test_edit_components() {
+    for &path in PATH_CORPUS {
+        let path = Utf8Path::new(path);
+        let components: Vec<_> = path.components().collect();
+
+        for index in 0..components.len() {
+            let mut expected = components.clone();
+            expected.remove(index);
+            let mut removed = path.to_path_buf();
+            removed.remove_component(index);
+            assert!(
+                removed.components().eq(expected.iter().copied()),
+                "removing component {index} from {path} yields {removed}",
+            );
+
+            let mut retained = path.to_path_buf();
+            let mut count = 0;
+            retained.retain_components(|_| {
+                count += 1;
+                count % 2 == index % 2
+            });
+            let expected = components
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| i % 2 != index % 2)
+                .map(|(_, c)| *c);
+            assert!(
+                retained.components().eq(expected),
+                "retaining components of {path} yields {retained}",
+            );
+        }
+
+        for index in 0..=components.len() {
+            // Components can't be inserted before or replace the prefix or root.
+            if let Some(Utf8Component::Prefix(_) | Utf8Component::RootDir) = components.get(index) {
+                continue;
+            }
+
+            let mut expected = components.clone();
+            expected.insert(index, Utf8Component::Normal("new"));
+            let mut inserted = path.to_path_buf();
+            inserted.insert_component(index, "new");
+            assert!(
+                inserted.components().eq(expected.iter().copied()),
+                "inserting a component at {index} into {path} yields {inserted}",
+            );
+
+            if index < components.len() {
+                expected.remove(index + 1);
+                let mut replaced = path.to_path_buf();
+                replaced.replace_component(index, "new");
+                assert!(
+                    replaced.components().eq(expected.iter().copied()),
+                    "replacing component {index} of {path} yields {replaced}",
+                );
+            }
+        }
+    }
+
+    // Inserting at the end appends, whether or not there's a trailing separator.
+    for (path, expected) in [("", "x/y"), ("a/b", "a/b/x/y"), ("a/b/", "a/b/x/y")] {
+        let mut path = Utf8PathBuf::from(path);
+        let index = path.components().count();
+        path.insert_component(index, Utf8Path::new("x").join("y").as_str());
+        assert_eq!(path, Utf8Path::new(expected));
+    }
+    #[cfg(unix)]
+    {
+        let mut path = Utf8PathBuf::from("/");
+        path.insert_component(1, "etc");
+        assert_eq!(path.as_str(), "/etc");
+    }
+}
+
+#[test]
+#[should_panic = "cannot insert a component with a prefix or root"]
+fn test_insert_component_absolute_at_end() {
+    let mut path = Utf8PathBuf::from("a/b");
+    path.insert_component(2, "/etc");
+}
+
+#[test]
+#[should_panic = "cannot insert a component with a prefix or root"]
+fn test_insert_component_absolute_in_middle() {
+    let mut path = Utf8PathBuf::from("a/b");
+    path.insert_component(1, "/etc");
+}
+
+#[test]
+#[should_panic = "cannot insert a component with a prefix or root"]
+fn test_replace_component_absolute() {
+    let mut path = Utf8PathBuf::from("a/b");
+    path.replace_component(0, "/etc");
+}
+
+#[test]
+#[should_panic = "cannot insert an empty component"]
+fn test_replace_component_empty() {
+    let mut path = Utf8PathBuf::from("a/b");
+    path.replace_component(1, "");
+}
+
+#[test]
+#[should_panic = "cannot insert an empty component"]
+fn test_insert_component_empty() {
+    let mut path = Utf8PathBuf::from("a/b");
+    path.insert_component(0, "");
+}
+
+#[test]
+fn test_cmp_natural() {
+    let numbered = [
+        "a", "a0", "a00", "a1", "a01", "a2", "a10", "a010", "a1b", "a1.9", "a1.10", "b",
+    ];
+    let paths: Vec<_> = PATH_CORPUS
+        .iter()
+        .chain(&numbered)
+        .map(Utf8Path::new)
+        .collect();
+
+    for &a in &paths {
+        for &b in &paths {
+            let ordering = a.cmp_natural(b);
+            assert_eq!(ordering == Ordering::Equal, a == b, "{a} and {b}");
+            assert_eq!(b.cmp_natural(a), ordering.reverse(), "{a} and {b}");
+            for &c in &paths {
+                if ordering != Ordering::Greater && b.cmp_natural(c) != Ordering::Greater {
+                    assert_ne!(a.cmp_natural(c), Ordering::Greater, "{a}, {b} and {c}");
+                }
+            }
+        }
+    }
+
+    let mut sorted = numbered.map(Utf8Path::new);
+    sorted.sort_by(|a, b| a.cmp_natural(b));
+    assert_eq!(
+        sorted,
+        [
+            "a", "a0", "a00", "a01", "a1", "a1.9", "a1.10", "a1b", "a2", "a010", "a10", "b"
+        ],
+    );
+}
+
+#[test]
+fn test_cmp_tree_order() {
+    let extra = ["a", "a/b", "a-b", "a.txt", "a/b/c", "b"];
+    let paths: Vec<_> = PATH_CORPUS
+        .iter()
+        .chain(&extra)
+        .map(Utf8Path::new)
+        .collect();
+
+    // cmp_tree_order is the same as Ord.
+    for &a in &paths {
+        for &b in &paths {
+            assert_eq!(a.cmp_tree_order(b), a.cmp(b), "{a:?} and {b:?}");
+        }
+    }
+
+    let mut sorted: Vec<_> = extra.iter().map(Utf8Path::new).collect();
+    sorted.sort_by(|a, b| a.cmp_tree_order(b));
+    assert_eq!(sorted, ["a", "a/b", "a/b/c", "a-b", "a.txt", "b"]);
+}
+
+#[test]
+fn test_sort_key() {
+    let extra = [
+        "a", "a/b", "a-b", "a.txt", "a\0", "a\0b", "a\u{1}", "a/\0", "é", "e",
+    ];
+    let paths: Vec<_> = PATH_CORPUS
+        .iter()
+        .chain(&extra)
+        .map(Utf8Path::new)
+        .collect();
+
+    for &a in &paths {
+        for &b in &paths {
+            let ordering = a.cmp(b);
+            assert_eq!(
+                a.sort_key().cmp(&b.sort_key()),
+                ordering,
+                "sort keys of {a:?} and {b:?}",
+            );
+        }
+    }
+}
+
+#[test]
+fn test_kind() {
+    for &path in PATH_CORPUS {
+        let path = Utf8Path::new(path);
+        let kind = path.kind();
+        assert_eq!(kind.is_absolute(), path.is_absolute(), "{path} is {kind:?}");
+        assert_eq!(
+            kind == Utf8PathKind::Relative,
+            path.components().next().map_or(true, |c| !matches!(
+                c,
+                Utf8Component::Prefix(_) | Utf8Component::RootDir
+            )),
+            "{path} is {kind:?}",
+        );
+    }
+}
+
+#[test]
+fn test_component_spans() {
+    for &path in PATH_CORPUS {
+        let path = Utf8Path::new(path);
+        let spans: Vec<_> = path.component_spans().collect();
+        assert!(
+            spans.iter().map(|(_, c)| *c).eq(path.components()),
+            "component spans of {path} have the same components",
+        );
+
+        for (range, component) in &spans {
+            let s = path
+                .as_str()
+                .get(range.clone())
+                .unwrap_or_else(|| panic!("span {range:?} of {path} is in bounds"));
+            match component {
+                // The root is either a single separator, or implied by a prefix on Windows.
+                Utf8Component::RootDir => {
+                    assert!(
+                        s.chars().all(std::path::is_separator) && s.len() <= 1,
+                        "span {range:?} of {path}"
+                    );
+                }
+                _ => assert_eq!(s, component.as_str(), "span {range:?} of {path}"),
+            }
+        }
+
+        let mut reversed: Vec<_> = path.component_spans().rev().collect();
+        reversed.reverse();
+        assert_eq!(reversed, spans, "component spans of {path} in reverse");
+    }
+}
+
+#[test]
+fn test_stable_hash() {
+    // These values must never change, since stable hashes may be persisted.
+    let cases: &[(&str, u64)] = &[
+        ("", 0xcbf2_9ce4_8422_2325),
+        ("src/lib.rs", 0x2fba_4152_af2c_3a3a),
+        ("/usr/lib", 0xd62a_a2c9_1057_58e0),
+        ("./a/../b", 0xe4ab_a5cd_5288_da6b),
+    ];
+    for &(path, expected) in cases {
+        assert_eq!(
+            Utf8Path::new(path).stable_hash(),
+            expected,
+            "stable hash of {path}: {:#x}",
+            Utf8Path::new(path).stable_hash(),
+        );
+    }
+
+    // Drive letters are compared case-insensitively on Windows.
+    #[cfg(windows)]
+    for (a, b) in [
+        ("c:\\foo", "C:\\foo"),
+        ("c:foo", "C:foo"),
+        ("\\\\?\\c:\\foo", "\\\\?\\C:\\foo"),
+    ] {
+        let (a, b) = (Utf8Path::new(a), Utf8Path::new(b));
+        assert_eq!(a, b);
+        assert_eq!(a.stable_hash(), b.stable_hash(), "{a} and {b}");
+    }
+
+    for &a in PATH_CORPUS {
+        for &b in PATH_CORPUS {
+            let (a, b) = (Utf8Path::new(a), Utf8Path::new(b));
+            if a == b {
+                assert_eq!(a.stable_hash(), b.stable_hash(), "{a} and {b}");
+            }
+        }
+    }
+}
+
+#[test]
+fn test_remapper() {
+    let remapper: Utf8PathRemapper = [
+        ("/a", "A"),
+        ("/a/b", "AB"),
+        ("/a/b", "AB2"),
+        ("/c", "C"),
+        ("/", "ROOT"),
+    ]
+    .into_iter()
+    .collect();
+    assert_eq!(remapper.rules().len(), 5);
+
+    let cases = [
+        ("/a", "A"),
+        ("/a/x", "A/x"),
+        ("/ab", "ROOT/ab"),
+        // Equally long rules: the one added last wins.
+        ("/a/b/x", "AB2/x"),
+        ("/c/d", "C/d"),
+        ("rel/path", "rel/path"),
+    ];
+    for (path, expected) in cases {
+        let remapped = remapper.remap(Utf8Path::new(path));
+        assert_eq!(remapped.as_str(), expected, "remapping {path}");
+        if path.starts_with('/') && !expected.starts_with("AB") {
+            assert_eq!(
+                remapper.unmap(&remapped),
+                Utf8Path::new(path),
+                "unmapping {path}"
+            );
+        }
+    }
+
+    // Both rules for /a/b map back to it.
+    assert_eq!(
+        remapper.unmap(Utf8Path::new("AB2/x")),
+        Utf8Path::new("/a/b/x")
+    );
+    assert_eq!(
+        remapper.unmap(Utf8Path::new("AB/x")),
+        Utf8Path::new("/a/b/x")
+    );
+
+    for &path in PATH_CORPUS {
+        let path = Utf8Path::new(path);
+        assert_eq!(Utf8PathRemapper::new().remap(path), path);
+    }
+}
+
+#[test]
+fn test_anchored_path() {
+    for &path in PATH_CORPUS {
+        let path = Utf8Path::new(path);
+        if path.has_root() {
+            continue;
+        }
+        let normalized = match path.normalize_lexically() {
+            Ok(normalized) => normalized,
+            Err(_) => {
+                assert!(
+                    Utf8AnchoredPath::new("base", path).is_err(),
+                    "{path} escapes base"
+                );
+                continue;
+            }
+        };
+        let anchored = Utf8AnchoredPath::new("base", path).expect("path is within base");
+        assert_eq!(anchored.base(), "base", "base for {path}");
+        assert_eq!(anchored.rel(), normalized, "rel for {path}");
+        assert_eq!(anchored.to_string(), anchored.rel().as_str(), "{path}");
+        assert_eq!(
+            anchored.as_path(),
+            Utf8Path::new("base").join(&normalized),
+            "full path for {path}"
+        );
+
+        let from_full = Utf8AnchoredPath::from_full("base", anchored.as_path())
+            .expect("full path starts with base");
+        assert_eq!(from_full, anchored, "from_full for {path}");
+    }
+
+    // Anchored paths can be passed to APIs that take AsRef<Path>.
+    let anchored = Utf8AnchoredPath::new("base", "a/b").unwrap();
+    let std_path: &Path = anchored.as_ref();
+    assert_eq!(std_path, Path::new("base/a/b"));
+    assert_eq!(anchored.file_name(), Some("b"));
+
+    let (base, rel) = anchored.into_parts();
+    assert_eq!((base.as_str(), rel.as_str()), ("base", "a/b"));
+}
+
+#[test]
+fn test_check_portable() {
+    use PortabilityIssueKind::*;
+    use PortabilityTarget::*;
+
+    // Every path in the corpus is portable to Linux and macOS, other than mixed separators.
+    for &path in PATH_CORPUS {
+        let issues = Utf8Path::new(path).check_portable(&[Linux, MacOs]);
+        let expected = if path.contains('/') && path.contains('\\') {
+            vec![MixedSeparators, MixedSeparators]
+        } else {
+            vec![]
+        };
+        let kinds: Vec<_> = issues
+            .into_iter()
+            .map(|issue| issue.kind().clone())
+            .collect();
+        assert_eq!(kinds, expected, "issues for {path}");
+    }
+
+    let name = |s: &str| s.to_owned();
+    let cases: &[(&str, &[PortabilityTarget], Vec<PortabilityIssueKind>)] = &[
+        ("src/lib.rs", PortabilityTarget::ALL, vec![]),
+        ("CON", &[Windows], vec![ReservedName { name: name("CON") }]),
+        (
+            "a/nul.tar.gz",
+            &[Windows],
+            vec![ReservedName {
+                name: name("nul.tar.gz"),
+            }],
+        ),
+        (
+            "com1 .txt",
+            &[Windows],
+            vec![ReservedName {
+                name: name("com1 .txt"),
+            }],
+        ),
+        (
+            "LPT\u{b9}",
+            &[Windows],
+            vec![ReservedName {
+                name: name("LPT\u{b9}"),
+            }],
+        ),
+        (
+            "COM0/COM10/CONSOLE/nul_/lpt0.txt",
+            &[Windows],
+            vec![
+                ReservedName { name: name("COM0") },
+                ReservedName {
+                    name: name("lpt0.txt"),
+                },
+            ],
+        ),
+        (
+            "a?b|c",
+            &[Windows, Linux],
+            vec![
+                ForbiddenChar {
+                    name: name("a?b|c"),
+                    ch: '?',
+                },
+                ForbiddenChar {
+                    name: name("a?b|c"),
+                    ch: '|',
+                },
+            ],
+        ),
+        (
+            "tab\there",
+            &[Linux, Windows],
+            vec![ControlChar {
+                name: name("tab\there"),
+                ch: '\t',
+            }],
+        ),
+        (
+            "nul\0byte",
+            &[Linux, Windows],
+            vec![
+                ControlChar {
+                    name: name("nul\0byte"),
+                    ch: '\0',
+                },
+                ControlChar {
+                    name: name("nul\0byte"),
+                    ch: '\0',
+                },
+            ],
+        ),
+        (
+            "dir./file ",
+            &[Windows],
+            vec![
+                TrailingDotOrSpace { name: name("dir.") },
+                TrailingDotOrSpace {
+                    name: name("file "),
+                },
+            ],
+        ),
+        ("./a/../b", &[Windows], vec![]),
+    ];
+    // A backslash in a file name splits it in two on Windows.
+    #[cfg(unix)]
+    {
+        let issues = Utf8Path::new("a\\b").check_portable(&[Windows]);
+        let kinds: Vec<_> = issues.iter().map(|issue| issue.kind().clone()).collect();
+        assert_eq!(
+            kinds,
+            [ForbiddenChar {
+                name: name("a\\b"),
+                ch: '\\'
+            }]
+        );
+        assert_eq!(issues[0].target(), Windows);
+    }
+
+    for (path, targets, expected) in cases {
+        let issues = Utf8Path::new(path).check_portable(targets);
+        let kinds: Vec<_> = issues
+            .into_iter()
+            .map(|issue| issue.kind().clone())
+            .collect();
+        assert_eq!(&kinds, expected, "issues for {path:?}");
+    }
+
+    // Lengths are measured in UTF-16 code units on Windows, and in bytes elsewhere.
+    let long_name = "\u{e9}".repeat(200);
+    let issues = Utf8Path::new(&long_name).check_portable(PortabilityTarget::ALL);
+    let summary: Vec<_> = issues
+        .iter()
+        .map(|issue| (issue.target(), issue.kind().clone()))
+        .collect();
+    assert_eq!(
+        summary,
+        [MacOs, Linux].map(|target| (
+            target,
+            NameTooLong {
+                name: long_name.clone(),
+                len: 400,
+                max: 255
+            }
+        ))
+    );
+
+    let long_path = ["abcdefghi"; 200].join("/");
+    let issues = Utf8Path::new(&long_path).check_portable(&[Windows, MacOs, Linux, Windows]);
+    let summary: Vec<_> = issues
+        .iter()
+        .map(|issue| (issue.target(), issue.kind().clone()))
+        .collect();
+    assert_eq!(
+        summary,
+        [(Windows, 259), (MacOs, 1023)]
+            .map(|(target, max)| (target, PathTooLong { len: 1999, max }))
+    );
+    assert_eq!(
+        issues[0].to_string(),
+        "path is too long (1999 > 259) (on Windows)"
+    );
+}
+
+#[test]
+fn test_sanitize_file_name() {
+    let options = SanitizeOptions::new();
+    let cases = [
+        ("", "_"),
+        (".", "_"),
+        ("..", "_"),
+        ("...hidden", "...hidden"),
+        ("a/b\\c", "a_b_c"),
+        ("tab\there\u{7f}", "tab_here_"),
+        ("what? <really> \"yes\"|no*", "what_ _really_ _yes__no_"),
+        ("trailing. . ", "trailing"),
+        ("NUL", "_NUL"),
+        ("aux .tar.gz", "_aux .tar.gz"),
+        ("COM10", "COM10"),
+        ("lpt0", "_lpt0"),
+        ("caf\u{e9}", "caf\u{e9}"),
+    ];
+    for (name, expected) in cases {
+        assert_eq!(
+            camino::sanitize_file_name(name, options),
+            expected,
+            "sanitizing {name:?}"
+        );
+    }
+
+    let options = SanitizeOptions::new()
+        .replacement('-')
+        .strip_leading_dots(true)
+        .max_len(8);
+    let cases = [
+        ("...hidden", "hidden"),
+        (".../x", "-x"),
+        ("long name.txt", "long.txt"),
+        ("a.verylongextension", "a.verylo"),
+        ("abcdefg .txt", "abcd.txt"),
+        ("con1.txt", "con1.txt"),
+        ("COM1.txt", "-COM.txt"),
+        // Multi-byte characters and combining marks aren't split.
+        ("\u{e9}\u{e9}\u{e9}\u{e9}\u{e9}", "\u{e9}\u{e9}\u{e9}\u{e9}"),
+        ("e\u{301}e\u{301}e\u{301}", "e\u{301}e\u{301}"),
+        ("ab\u{1f44d}\u{1f3fd}x", "ab"),
+        ("\u{1f469}\u{200d}\u{1f4bb}.png", "-"),
+    ];
+    for (name, expected) in cases {
+        assert_eq!(
+            camino::sanitize_file_name(name, options),
+            expected,
+            "sanitizing {name:?}"
+        );
+    }
+
+    // Grapheme clusters aren't split.
+    let cases = [
+        // Flags are pairs of regional indicators, 4 bytes each.
+        (
+            "\u{1f1ef}\u{1f1f5}\u{1f1fa}\u{1f1f8}",
+            12,
+            "\u{1f1ef}\u{1f1f5}",
+        ),
+        ("\u{1f1ef}\u{1f1f5}\u{1f1fa}\u{1f1f8}", 7, "_"),
+        // Hangul syllables made up of conjoining jamo, 3 bytes each.
+        (
+            "\u{1100}\u{1161}\u{11a8}\u{1100}\u{1161}",
+            12,
+            "\u{1100}\u{1161}\u{11a8}",
+        ),
+        ("\u{1100}\u{1161}\u{11a8}\u{1100}\u{1161}", 7, "_"),
+        ("\u{ac00}\u{11a8}\u{ac01}\u{11a8}", 9, "\u{ac00}\u{11a8}"),
+        // Devanagari spacing vowel signs.
+        ("\u{915}\u{93f}\u{915}\u{93f}", 9, "\u{915}\u{93f}"),
+        ("\u{915}\u{93e}.txt", 7, "\u{915}\u{93e}"),
+        // Hebrew and Arabic points.
+        (
+            "\u{5d1}\u{5b0}\u{5bc}\u{5d1}\u{5b0}\u{5bc}",
+            10,
+            "\u{5d1}\u{5b0}\u{5bc}",
+        ),
+        ("\u{628}\u{650}\u{628}\u{650}", 6, "\u{628}\u{650}"),
+        // Bengali, Thai and Tamil vowel signs, 3 bytes each.
+        ("\u{995}\u{9bf}\u{995}\u{9bf}", 4, "_"),
+        ("\u{995}\u{9bf}\u{995}\u{9bf}", 9, "\u{995}\u{9bf}"),
+        ("\u{e01}\u{e33}\u{e01}\u{e33}", 4, "_"),
+        ("\u{e01}\u{e33}\u{e01}\u{e33}", 9, "\u{e01}\u{e33}"),
+        ("\u{b95}\u{bbf}\u{b95}\u{bbf}", 4, "_"),
+        ("\u{b95}\u{bbf}\u{b95}\u{bbf}", 9, "\u{b95}\u{bbf}"),
+    ];
+    for (name, max_len, expected) in cases {
+        let options = SanitizeOptions::new().max_len(max_len);
+        assert_eq!(
+            camino::sanitize_file_name(name, options),
+            expected,
+            "sanitizing {name:?} to {max_len} bytes"
+        );
+    }
+
+    // Sanitized names are always portable single components, including names truncated to the
+    // maximum length.
+    let long_names = ["a".repeat(300), format!("{}.txt", "\u{e9}".repeat(200))];
+    let paths = PATH_CORPUS
+        .iter()
+        .copied()
+        .chain(long_names.iter().map(String::as_str));
+    for path in paths {
+        for options in [options, SanitizeOptions::new()] {
+            let name = camino::sanitize_file_name(path, options);
+            let name = Utf8Path::new(&name);
+            assert_eq!(
+                name.components().collect::<Vec<_>>(),
+                [Utf8Component::Normal(name.as_str())],
+                "{path} sanitized to {name}"
+            );
+            assert_eq!(
+                name.check_portable(PortabilityTarget::ALL),
+                [],
+                "{path} sanitized to {name}"
+            );
+        }
+    }
+}
+
+#[test]
+#[should_panic = "max_len must be between 1 and 255"]
+fn test_sanitize_max_len_too_long() {
+    let _ = SanitizeOptions::new().max_len(256);
+}